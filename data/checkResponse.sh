@@ -278,7 +278,7 @@ orderdata_digest_file="${output_dir_name}/tmp/orderdata_digestcheck_$timestamp.b
 openssl dgst -sha256 -binary  $orderdata_bin_file > "$orderdata_digest_file"
 orderdata_signature_hex_output_file=${output_dir_name}/${xml_file_stem}-Witness.hex
 
-# Witness file needs to be present, generated before this script by hyperfridge or here
+# Witness file needs to be present, generated before this script by the witness module (see docs/witness.md) or here
 # if the witness-private key is present; sign the payload. 
 if [ -f "$witness_pem" ]; then
     echo "generating witness signature"
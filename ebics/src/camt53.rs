@@ -0,0 +1,562 @@
+//! ISO 20022 camt.053 bank to customer statements as delivered in the order data of an
+//! EBICS response, versions camt.053.001.02, .04 and .08.
+
+use crate::EbicsError;
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+/// GrpHdr structure of a Camt53 XML respose
+#[derive(Debug, Default)]
+pub struct Document {
+    pub grp_hdr: GrpHdr,     // creation time
+    pub msg_version: String, // e.g. camt.053.001.04, from the namespace of <Document>
+    pub stmts: Vec<Stmt>,
+}
+
+/// Supported versions of camt.053, the layout of entries differs between versions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Camt53Version {
+    /// amount of a transaction only in <AmtDtls>, no <CdtDbtInd> in <TxDtls>
+    V02,
+    V04,
+    /// <Sts><Cd>, parties in <Dbtr><Pty>
+    V08,
+}
+
+impl Camt53Version {
+    const NAMESPACE: &'static str = "urn:iso:std:iso:20022:tech:xsd:";
+
+    /// version from the namespace of <Document>
+    pub fn from_namespace(namespace: &str) -> Result<Camt53Version, EbicsError> {
+        match namespace.strip_prefix(Self::NAMESPACE) {
+            Some("camt.053.001.02") => Ok(Camt53Version::V02),
+            Some("camt.053.001.04") => Ok(Camt53Version::V04),
+            Some("camt.053.001.08") => Ok(Camt53Version::V08),
+            _ => Err(EbicsError::Camt53(format!(
+                "unsupported camt.053 version {}",
+                namespace
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Camt53Version::V02 => "camt.053.001.02",
+            Camt53Version::V04 => "camt.053.001.04",
+            Camt53Version::V08 => "camt.053.001.08",
+        }
+    }
+}
+
+/// GrpHdr structure of a Camt53 XML respose
+#[derive(Debug, Default)]
+pub struct GrpHdr {
+    pub cre_dt_tm: String, // creating time
+    pub msg_id: String,    // unique ebics message id - identifies ebics xml message
+    pub pg_nb: i8,
+    pub last_pg_ind: bool,
+}
+
+/// Stmt structure of a Camt53 XML respose
+#[derive(Debug, Default)]
+pub struct Stmt {
+    pub id: String, // <Stmt><Id>, same on all pages of a paginated statement
+    pub elctrnc_seq_nb: String,
+    pub iban: String,
+    pub othr_id: String,       // <Othr><Id> for accounts without IBAN
+    pub othr_schme_nm: String, // <Othr><SchmeNm> code or proprietary name
+    pub ccy: String,           // currency of the account, may be empty
+    pub cre_dt_tm: String,     // creation time
+    pub fr_dt_tm: String,
+    pub to_dt_tm: String,
+    pub balances: Vec<Balance>,
+    pub txs_summry: Option<TxsSummry>, // <TxsSummry> as delivered by the bank
+    pub ntries: Vec<Ntry>,
+}
+
+/// <TxsSummry> of a statement, numbers and sums as in the camt53 file - empty if not given
+#[derive(Debug, Default, Clone)]
+pub struct TxsSummry {
+    pub nb_of_ntries: String,
+    pub sum: String,
+    pub cdt_nb_of_ntries: String,
+    pub cdt_sum: String,
+    pub dbt_nb_of_ntries: String,
+    pub dbt_sum: String,
+}
+
+/// Holds data from Camt53 XML file - a single transaction
+#[allow(non_snake_case)]
+#[derive(Debug, Default)]
+pub struct Ntry {
+    pub cdtDbtInd: String, // cdt_dbt_ind  - creit or debit indicator - plus or minus of the balance
+    pub sts: String,
+    pub rvslInd: bool, // reversal of a previous entry
+    pub ccy: String,   // currency
+    pub amt: String,
+    pub btchNbOfTxs: Option<usize>, // <NtryDtls><Btch><NbOfTxs> for batch bookings
+    pub addtlNtryInf: Option<String>, // <AddtlNtryInf>, free text of the bank for the entry
+    pub bkTxCd: BkTxCd,
+    pub txDtls: Vec<TxDtls>,
+}
+
+/// Bank transaction code of an entry, e.g. PMNT/RCDT/ESCT for a received SEPA credit
+/// transfer, PMNT/CCRD/POSD for a card payment or ACMT/MDOP/INTR for interest
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BkTxCd {
+    pub domn: String,     // <Domn><Cd>
+    pub fmly: String,     // <Domn><Fmly><Cd>
+    pub sub_fmly: String, // <Domn><Fmly><SubFmlyCd>
+    pub prtry: String,    // <Prtry><Cd>, e.g. SWIFT transaction type NTRF or a bank code
+}
+
+impl BkTxCd {
+    /// structured code as DOMN/FMLY/SUBFMLY, empty if the bank only delivers <Prtry>
+    pub fn code(&self) -> String {
+        [&self.domn, &self.fmly, &self.sub_fmly]
+            .iter()
+            .filter(|code| !code.is_empty())
+            .map(|code| code.as_str())
+            .collect::<Vec<&str>>()
+            .join("/")
+    }
+}
+
+impl Ntry {
+    /// the individual transactions of the entry - the transaction details of a batch booking,
+    /// or the entry itself (with <AddtlNtryInf> as additional information) if the bank does
+    /// not deliver details
+    pub fn transactions(&self) -> Vec<TxDtls> {
+        if !self.txDtls.is_empty() {
+            return self.txDtls.clone();
+        }
+        vec![TxDtls {
+            AmtCcy: self.ccy.clone(),
+            AmtValue: self.amt.clone(),
+            CdtDbtInd: self.cdtDbtInd.clone(),
+            AddtlTxInf: self.addtlNtryInf.clone(),
+            ..Default::default()
+        }]
+    }
+}
+
+/// Holds data from Camt53 XML file - transaction details
+#[allow(non_snake_case)]
+#[derive(Debug, Default, Clone)]
+pub struct TxDtls {
+    pub AmtCcy: String,
+    pub AmtValue: String,
+    pub CdtDbtInd: String,
+    pub DbtrNm: String,
+    //Debitor
+    pub DbtrStrtNm: String,
+    pub DbtrBldgNb: String,
+    pub DbtrPstCd: String,
+    pub DbtrTwnNm: String,
+    pub DbtrCtry: Option<String>,
+    pub DbtrAcctIBAN: String,
+    //Creditor
+    pub CdtrNm: String,
+    pub CdtrStrtNm: String,
+    pub CdtrBldgNb: String,
+    pub CdtrPstCd: String,
+    pub CdtrTwnNm: String,
+    pub CdtrCtry: Option<String>,
+    pub CdtrAcctIBAN: String,
+    //
+    pub RmtInfUstrd: Option<String>,
+    pub AddtlTxInf: Option<String>,
+}
+
+/// Balance structure of a Camt53 XML respose
+/// code or proprietory - OPBD = opening balance,CLBD is closing balance
+/// cdt_dbt_ind  - creit or debit indicator - plus or minus of the balance
+#[derive(Debug, Default)]
+pub struct Balance {
+    pub cd: String, // code or proprietory - OPBD = opening balance,CLBD is closing balance
+    pub ccy: String, // currency
+    pub amt: String,
+    pub dt: String,
+    pub cdt_dbt_ind: String, // cdt_dbt_ind  - creit or debit indicator - plus or minus of the balance
+}
+
+/// parses a Camt53 File which is decrypted and decompressed from the payload which is stored
+/// as base64 in the Ebics Response XML.
+/// It get information from ISO20022 camt53 which hold bank data.
+pub fn parse_camt53(camt53_file: &str) -> Result<Document, EbicsError> {
+    // variables to hold the current tag and the tag stack
+    let mut tag_stack: Vec<String> = Vec::new();
+    let mut current_balance = Balance::default();
+    let mut grp_header = GrpHdr::default();
+    let mut current_stmt = Stmt::default();
+    let mut current_ntry = Ntry::default();
+    let mut current_tx_dtls = TxDtls::default();
+    let mut current_tag = String::new();
+    let mut doc: Document = Document::default();
+    let mut version: Option<Camt53Version> = None;
+    // prefix of <Document>, e.g. ns for <ns:Document xmlns:ns="..">
+    let mut document_prefix = String::new();
+
+    let tokens = Tokenizer::from(camt53_file);
+    // to better understand what is does look at the file which is parsed.
+    // e.g. in data/response_template/camt53/*
+    for token in tokens {
+        match token {
+            // set current tag
+            Ok(Token::ElementStart { prefix, local, .. }) => {
+                if tag_stack.is_empty() && local == "Document" {
+                    document_prefix = prefix.to_string();
+                }
+                current_tag = local.to_string();
+                tag_stack.push(local.to_string());
+                // print_verbose!("   open tag  as_str {:?} ", local.as_str());
+            }
+            Ok(Token::ElementEnd { end, .. }) => {
+                // empty elements like <Ctry/> have no closing tag
+                if let ElementEnd::Empty = end {
+                    tag_stack.pop();
+                }
+                if let ElementEnd::Close(.., local) = end {
+                    if let Some(_tag) = tag_stack.pop() {
+                        // print_verbose!("End Tag: {}", _tag);
+                    };
+                    if local == "Bal" {
+                        current_stmt.balances.push(current_balance);
+                        current_balance = Balance::default();
+                    } else if local == "TxDtls" {
+                        // camt.053.001.02 has the indicator only on the entry
+                        if current_tx_dtls.CdtDbtInd.is_empty() {
+                            current_tx_dtls.CdtDbtInd = current_ntry.cdtDbtInd.clone();
+                        }
+                        current_ntry
+                            .txDtls
+                            .push(std::mem::take(&mut current_tx_dtls));
+                    } else if local == "Ntry" {
+                        current_stmt.ntries.push(std::mem::take(&mut current_ntry));
+                    } else if local == "Stmt" {
+                        doc.stmts.push(current_stmt);
+                        current_stmt = Stmt::default();
+                    }
+                }
+            }
+            Ok(Token::Text { text }) => {
+                if let Some(_current_tag) = tag_stack.last() {
+                    //print_verbose!("Text for {}: {}", _current_tag, text);
+                };
+
+                //<GrpHdr><MsgId>35e75effeaa74f579f97c8121bfa68ad</MsgId><CreDtTm>2023-11-29T22:54:31.6579278+01:00</CreDtTm><MsgPgntn><PgNb>1</PgNb><LastPgInd>true</LastPgInd></MsgPgntn></GrpHdr>
+                if tag_stack.starts_with(&[
+                    "Document".to_string(),
+                    "BkToCstmrStmt".to_string(),
+                    "GrpHdr".to_string(),
+                ]) {
+                    if tag_stack.ends_with(&["MsgId".to_string()]) {
+                        grp_header.msg_id = text.to_string();
+                    }
+                    if tag_stack.ends_with(&["CreDtTm".to_string()]) {
+                        grp_header.cre_dt_tm = text.to_string();
+                    }
+                    if tag_stack.ends_with(&["PgNb".to_string()]) {
+                        grp_header.pg_nb = text.as_str().parse::<i8>().map_err(|_| {
+                            EbicsError::Camt53(format!("invalid PgNb {}", text.as_str()))
+                        })?;
+                    }
+                    if tag_stack.ends_with(&["LastPgInd".to_string()]) {
+                        grp_header.last_pg_ind = text.as_str().parse::<bool>().map_err(|_| {
+                            EbicsError::Camt53(format!("invalid LastPgInd {}", text.as_str()))
+                        })?;
+                    }
+                };
+
+                // parse bank account tags - may be multiple.
+                if tag_stack.starts_with(&[
+                    "Document".to_string(),
+                    "BkToCstmrStmt".to_string(),
+                    "Stmt".to_string(),
+                ]) {
+                    if tag_stack.ends_with(&[
+                        "Acct".to_string(),
+                        "Id".to_string(),
+                        "IBAN".to_string(),
+                    ]) {
+                        current_stmt.iban = text.to_string();
+                    };
+                    // accounts without IBAN, e.g. US or custody accounts
+                    // <Acct><Id><Othr><Id>123456789</Id><SchmeNm><Cd>BBAN</Cd></SchmeNm></Othr></Id></Acct>
+                    if path_ends_with(&tag_stack, &["Stmt", "Acct", "Id", "Othr", "Id"]) {
+                        current_stmt.othr_id = text.to_string();
+                    };
+                    if path_ends_with(&tag_stack, &["Stmt", "Acct", "Id", "Othr", "SchmeNm", "Cd"])
+                        || path_ends_with(
+                            &tag_stack,
+                            &["Stmt", "Acct", "Id", "Othr", "SchmeNm", "Prtry"],
+                        )
+                    {
+                        current_stmt.othr_schme_nm = text.to_string();
+                    };
+                    // <Acct><Id><IBAN>..</IBAN></Id><Ccy>CHF</Ccy></Acct>
+                    if path_ends_with(&tag_stack, &["Stmt", "Acct", "Ccy"]) {
+                        current_stmt.ccy = text.to_string();
+                    };
+                    // <BkToCstmrStmt> <Stmt> <Id>13a8a34b3a7a45bb84fb2c39af620f54</Id>
+                    if path_ends_with(&tag_stack, &["BkToCstmrStmt", "Stmt", "Id"]) {
+                        current_stmt.id = text.to_string();
+                    };
+                    // <BkToCstmrStmt> <Stmt> <ElctrncSeqNb>247</ElctrncSeqNb>
+                    if tag_stack.ends_with(&["ElctrncSeqNb".to_string()]) {
+                        current_stmt.elctrnc_seq_nb = text.to_string();
+                    };
+                    if tag_stack.ends_with(&["CreDtTm".to_string()]) {
+                        current_stmt.cre_dt_tm = text.to_string();
+                    };
+                    //<FrToDt> <FrDtTm>2023-11-29T00:00:00</FrDtTm><ToDtTm>2023-11-29T00:00:00</ToDtTm></FrToD
+                    if tag_stack.ends_with(&["FrToDt".to_string(), "FrDtTm".to_string()]) {
+                        current_stmt.fr_dt_tm = text.to_string();
+                    };
+                    if tag_stack.ends_with(&["FrToDt".to_string(), "ToDtTm".to_string()]) {
+                        current_stmt.to_dt_tm = text.to_string();
+                    };
+
+                    //<BkToCstmrStmt> <Stmt>
+                    //<Bal><Tp> <CdOrPrtry>Cd>OPBD</Cd></CdOrPrtry></Tp><Amt Ccy="CHF">31709.14</Amt><CdtDbtInd>CRDT</CdtDbtInd><Dt><Dt>2023-11-29</Dt></Dt></Bal>
+                    //<Bal><Tp> CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp><Amt Ccy="CHF">31709.09</Amt><CdtDbtInd>CRDT</CdtDbtInd><Dt><Dt>2023-11-29</Dt></Dt></Bal>
+
+                    if tag_stack.ends_with(&[
+                        "Bal".to_string(),
+                        "Tp".to_string(),
+                        "CdOrPrtry".to_string(),
+                        "Cd".to_string(),
+                    ]) {
+                        current_balance.cd = text.to_string();
+                    }
+                    // proprietary balance types <CdOrPrtry><Prtry>..</Prtry></CdOrPrtry>
+                    if path_ends_with(&tag_stack, &["Bal", "Tp", "CdOrPrtry", "Prtry"]) {
+                        current_balance.cd = text.to_string();
+                    }
+                    if tag_stack.ends_with(&["Bal".to_string(), "Amt".to_string()]) {
+                        current_balance.amt = text.to_string();
+                    }
+                    if tag_stack.ends_with(&["Bal".to_string(), "Dt".to_string(), "Dt".to_string()])
+                    {
+                        current_balance.dt = text.to_string();
+                    }
+                    // intraday balances (ITBD) may have <Dt><DtTm>..</DtTm></Dt>
+                    if path_ends_with(&tag_stack, &["Bal", "Dt", "DtTm"]) {
+                        current_balance.dt = text.to_string();
+                    }
+                    if tag_stack.ends_with(&["Bal".to_string(), "CdtDbtInd".to_string()]) {
+                        current_balance.cdt_dbt_ind = text.to_string();
+                    }
+
+                    // <Ntry><Amt Ccy="CHF">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts> ...
+                    if path_ends_with(&tag_stack, &["Stmt", "Ntry", "Amt"]) {
+                        current_ntry.amt = text.to_string();
+                    }
+                    if path_ends_with(&tag_stack, &["Stmt", "Ntry", "CdtDbtInd"]) {
+                        current_ntry.cdtDbtInd = text.to_string();
+                    }
+                    // <Sts>BOOK</Sts>, camt.053.001.08 uses <Sts><Cd>BOOK</Cd></Sts>
+                    let sts_path: &[&str] = match version {
+                        Some(Camt53Version::V08) => &["Ntry", "Sts", "Cd"],
+                        _ => &["Ntry", "Sts"],
+                    };
+                    if path_ends_with(&tag_stack, sts_path) {
+                        current_ntry.sts = text.to_string();
+                    }
+                    // <BkTxCd><Domn><Cd>PMNT</Cd><Fmly><Cd>RCDT</Cd><SubFmlyCd>ESCT</SubFmlyCd></Fmly></Domn>
+                    // <Prtry><Cd>NTRF+166</Cd></Prtry></BkTxCd> of the entry, not of its TxDtls
+                    if path_ends_with(&tag_stack, &["Ntry", "BkTxCd", "Domn", "Cd"]) {
+                        current_ntry.bkTxCd.domn = text.to_string();
+                    }
+                    if path_ends_with(&tag_stack, &["Ntry", "BkTxCd", "Domn", "Fmly", "Cd"]) {
+                        current_ntry.bkTxCd.fmly = text.to_string();
+                    }
+                    if path_ends_with(&tag_stack, &["Ntry", "BkTxCd", "Domn", "Fmly", "SubFmlyCd"])
+                    {
+                        current_ntry.bkTxCd.sub_fmly = text.to_string();
+                    }
+                    if path_ends_with(&tag_stack, &["Ntry", "BkTxCd", "Prtry", "Cd"]) {
+                        current_ntry.bkTxCd.prtry = text.to_string();
+                    }
+                    // <TxsSummry><TtlNtries><NbOfNtries>2</NbOfNtries><Sum>0.10</Sum></TtlNtries>
+                    // <TtlCdtNtries>..</TtlCdtNtries><TtlDbtNtries>..</TtlDbtNtries></TxsSummry>
+                    if tag_stack.iter().any(|tag| tag == "TxsSummry") {
+                        let summary = current_stmt
+                            .txs_summry
+                            .get_or_insert_with(TxsSummry::default);
+                        if path_ends_with(&tag_stack, &["TxsSummry", "TtlNtries", "NbOfNtries"]) {
+                            summary.nb_of_ntries = text.to_string();
+                        } else if path_ends_with(&tag_stack, &["TxsSummry", "TtlNtries", "Sum"]) {
+                            summary.sum = text.to_string();
+                        } else if path_ends_with(&tag_stack, &["TtlCdtNtries", "NbOfNtries"]) {
+                            summary.cdt_nb_of_ntries = text.to_string();
+                        } else if path_ends_with(&tag_stack, &["TtlCdtNtries", "Sum"]) {
+                            summary.cdt_sum = text.to_string();
+                        } else if path_ends_with(&tag_stack, &["TtlDbtNtries", "NbOfNtries"]) {
+                            summary.dbt_nb_of_ntries = text.to_string();
+                        } else if path_ends_with(&tag_stack, &["TtlDbtNtries", "Sum"]) {
+                            summary.dbt_sum = text.to_string();
+                        }
+                    }
+
+                    // <NtryDtls><Btch><NbOfTxs>1</NbOfTxs></Btch>
+                    if path_ends_with(&tag_stack, &["Ntry", "NtryDtls", "Btch", "NbOfTxs"]) {
+                        current_ntry.btchNbOfTxs =
+                            Some(text.as_str().parse::<usize>().map_err(|_| {
+                                EbicsError::Camt53(format!("invalid NbOfTxs {}", text.as_str()))
+                            })?);
+                    }
+                    // <RvslInd>true</RvslInd>
+                    if path_ends_with(&tag_stack, &["Stmt", "Ntry", "RvslInd"]) {
+                        current_ntry.rvslInd = text.as_str() == "true";
+                    }
+                    if path_ends_with(&tag_stack, &["Stmt", "Ntry", "AddtlNtryInf"]) {
+                        current_ntry.addtlNtryInf = Some(text.to_string());
+                    }
+
+                    // <NtryDtls><TxDtls><Amt Ccy="CHF">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd>
+                    // <RltdPties><Dbtr><Nm>element36 AG</Nm><PstlAdr>..</PstlAdr></Dbtr><DbtrAcct><Id><IBAN>..</IBAN></Id></DbtrAcct>
+                    // <Cdtr>..</Cdtr><CdtrAcct>..</CdtrAcct></RltdPties><RmtInf><Ustrd>test</Ustrd></RmtInf><AddtlTxInf>test</AddtlTxInf></TxDtls>
+                    if tag_stack.iter().any(|tag| tag == "TxDtls") {
+                        let version = version.ok_or_else(|| {
+                            EbicsError::Camt53(
+                                "camt53 document without camt.053 namespace".to_string(),
+                            )
+                        })?;
+                        let tag_path = version_path(&tag_stack, version);
+                        parse_tx_dtls(&tag_path, version, text.as_str(), &mut current_tx_dtls);
+                    }
+                };
+            }
+            // <Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.04"> or the
+            // namespace of the prefix <ns:Document xmlns:ns="urn:iso:std:iso:20022:..">
+            Ok(Token::Attribute {
+                prefix,
+                local,
+                value,
+                ..
+            }) if current_tag == "Document"
+                && tag_stack.len() == 1
+                && ((document_prefix.is_empty() && prefix.is_empty() && local == "xmlns")
+                    || (!document_prefix.is_empty()
+                        && prefix == "xmlns"
+                        && local == document_prefix.as_str())) =>
+            {
+                let camt53_version = Camt53Version::from_namespace(&value)?;
+                doc.msg_version = camt53_version.name().to_string();
+                version = Some(camt53_version);
+            }
+            Ok(Token::Attribute { local, value, .. }) if (current_tag == "Amt") => {
+                if tag_stack.ends_with(&[
+                    "BkToCstmrStmt".to_string(),
+                    "Stmt".to_string(),
+                    "Bal".to_string(),
+                    "Amt".to_string(),
+                ]) && local.as_str() == "Ccy"
+                {
+                    current_balance.ccy = value.to_string();
+                }
+                if local.as_str() == "Ccy" {
+                    if path_ends_with(&tag_stack, &["Stmt", "Ntry", "Amt"]) {
+                        current_ntry.ccy = value.to_string();
+                    } else if path_ends_with(&tag_stack, tx_amt_path(version))
+                        || (path_ends_with(&tag_stack, &["TxDtls", "AmtDtls", "TxAmt", "Amt"])
+                            && current_tx_dtls.AmtCcy.is_empty())
+                    {
+                        current_tx_dtls.AmtCcy = value.to_string();
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(e) => return Err(EbicsError::Camt53(format!("error parsing camt53 {}", e))),
+        }
+    }
+
+    if version.is_none() {
+        return Err(EbicsError::Camt53(
+            "camt53 document without camt.053 namespace".to_string(),
+        ));
+    }
+    doc.grp_hdr = grp_header;
+    Ok(doc)
+}
+
+/// true if the innermost tags of `tag_stack` are `path`, also used for other XML order data
+pub fn path_ends_with(tag_stack: &[String], path: &[&str]) -> bool {
+    tag_stack.len() >= path.len()
+        && tag_stack[tag_stack.len() - path.len()..]
+            .iter()
+            .zip(path.iter())
+            .all(|(tag, expected)| tag == expected)
+}
+
+/// path of the amount of a transaction in <TxDtls>
+fn tx_amt_path(version: Option<Camt53Version>) -> &'static [&'static str] {
+    match version {
+        Some(Camt53Version::V02) => &["TxDtls", "AmtDtls", "TxAmt", "Amt"],
+        _ => &["TxDtls", "Amt"],
+    }
+}
+
+/// maps the tags to the layout of camt.053.001.04 - camt.053.001.08 wraps the parties
+/// in <Pty>, e.g. <Dbtr><Pty><Nm>
+fn version_path(tag_stack: &[String], version: Camt53Version) -> Vec<String> {
+    if version != Camt53Version::V08 {
+        return tag_stack.to_vec();
+    }
+    let mut path: Vec<String> = Vec::with_capacity(tag_stack.len());
+    for tag in tag_stack {
+        let in_party = path
+            .last()
+            .is_some_and(|parent| parent == "Dbtr" || parent == "Cdtr");
+        if !(in_party && tag == "Pty") {
+            path.push(tag.clone());
+        }
+    }
+    path
+}
+
+/// fills the transaction details of an entry from a text node within <TxDtls>
+fn parse_tx_dtls(tag_stack: &[String], version: Camt53Version, text: &str, tx_dtls: &mut TxDtls) {
+    if path_ends_with(tag_stack, tx_amt_path(Some(version))) {
+        tx_dtls.AmtValue = text.to_string();
+    } else if path_ends_with(tag_stack, &["TxDtls", "AmtDtls", "TxAmt", "Amt"])
+        && tx_dtls.AmtValue.is_empty()
+    {
+        // <TxDtls><Amt> is optional, the amount in the account currency is also in <AmtDtls>
+        tx_dtls.AmtValue = text.to_string();
+    } else if path_ends_with(tag_stack, &["TxDtls", "CdtDbtInd"]) {
+        tx_dtls.CdtDbtInd = text.to_string();
+    } else if path_ends_with(tag_stack, &["RltdPties", "Dbtr", "Nm"]) {
+        tx_dtls.DbtrNm = text.to_string();
+    } else if path_ends_with(tag_stack, &["Dbtr", "PstlAdr", "StrtNm"]) {
+        tx_dtls.DbtrStrtNm = text.to_string();
+    } else if path_ends_with(tag_stack, &["Dbtr", "PstlAdr", "BldgNb"]) {
+        tx_dtls.DbtrBldgNb = text.to_string();
+    } else if path_ends_with(tag_stack, &["Dbtr", "PstlAdr", "PstCd"]) {
+        tx_dtls.DbtrPstCd = text.to_string();
+    } else if path_ends_with(tag_stack, &["Dbtr", "PstlAdr", "TwnNm"]) {
+        tx_dtls.DbtrTwnNm = text.to_string();
+    } else if path_ends_with(tag_stack, &["Dbtr", "PstlAdr", "Ctry"]) {
+        tx_dtls.DbtrCtry = Some(text.to_string());
+    } else if path_ends_with(tag_stack, &["DbtrAcct", "Id", "IBAN"]) {
+        tx_dtls.DbtrAcctIBAN = text.to_string();
+    } else if path_ends_with(tag_stack, &["RltdPties", "Cdtr", "Nm"]) {
+        tx_dtls.CdtrNm = text.to_string();
+    } else if path_ends_with(tag_stack, &["Cdtr", "PstlAdr", "StrtNm"]) {
+        tx_dtls.CdtrStrtNm = text.to_string();
+    } else if path_ends_with(tag_stack, &["Cdtr", "PstlAdr", "BldgNb"]) {
+        tx_dtls.CdtrBldgNb = text.to_string();
+    } else if path_ends_with(tag_stack, &["Cdtr", "PstlAdr", "PstCd"]) {
+        tx_dtls.CdtrPstCd = text.to_string();
+    } else if path_ends_with(tag_stack, &["Cdtr", "PstlAdr", "TwnNm"]) {
+        tx_dtls.CdtrTwnNm = text.to_string();
+    } else if path_ends_with(tag_stack, &["Cdtr", "PstlAdr", "Ctry"]) {
+        tx_dtls.CdtrCtry = Some(text.to_string());
+    } else if path_ends_with(tag_stack, &["CdtrAcct", "Id", "IBAN"]) {
+        tx_dtls.CdtrAcctIBAN = text.to_string();
+    } else if path_ends_with(tag_stack, &["RmtInf", "Ustrd"]) {
+        tx_dtls.RmtInfUstrd = Some(text.to_string());
+    } else if path_ends_with(tag_stack, &["TxDtls", "AddtlTxInf"]) {
+        tx_dtls.AddtlTxInf = Some(text.to_string());
+    }
+}
@@ -0,0 +1,177 @@
+//! Errors of hyperfridge, shared by the guest, the host and the verifier. The guest fails with
+//! the error as panic message, e.g. `[digest_mismatch] hash of all ...`; the prover returns
+//! that message to the host, which recovers the error with `from_guest` and exits with its
+//! `exit_code`.
+
+use crate::EbicsError;
+use std::fmt;
+
+/// What went wrong, each with its own exit code of host and verifier
+#[derive(Debug, Clone, PartialEq)]
+pub enum HyperfridgeError {
+    /// invalid command line argument or guest input, e.g. a malformed key or IBAN
+    Input(String),
+    /// a file could not be read or written
+    Io(String),
+    /// malformed EbicsResponse, camt53, MT940, pain.002, disclosure policy or FX rates
+    Parse(String),
+    /// the bank did not mark the EBICS response as successful
+    ReturnCode(String),
+    /// the hash of the authenticated tags does not match <DigestValue>
+    DigestMismatch(String),
+    /// the signature of the bank over <SignedInfo> is invalid
+    SignatureInvalid(String),
+    /// the transaction key or the order data could not be decrypted
+    Decryption(String),
+    /// not enough valid witness signatures, or the signature of the FX rates is invalid
+    WitnessInvalid(String),
+    /// no statement for a requested account, or the IBAN is not in the receipt
+    IbanNotFound(String),
+    /// no outgoing transfer with the bind_to value in AddtlTxInf
+    BindingNotProven(String),
+    /// the entries do not match <TxsSummry> or the details of a batch booking
+    Inconsistent(String),
+    /// the prover failed for another reason, e.g. a panic in the guest
+    Proof(String),
+    /// the receipt is invalid or does not match what the verifier expects
+    Verification(String),
+}
+
+impl HyperfridgeError {
+    /// all kinds, to recover the error from the panic message of the guest
+    const KINDS: [&'static str; 13] = [
+        "input",
+        "io",
+        "parse",
+        "return_code",
+        "digest_mismatch",
+        "signature_invalid",
+        "decryption",
+        "witness_invalid",
+        "iban_not_found",
+        "binding_not_proven",
+        "inconsistent",
+        "proof",
+        "verification",
+    ];
+
+    fn new(kind: &str, message: String) -> HyperfridgeError {
+        match kind {
+            "input" => HyperfridgeError::Input(message),
+            "io" => HyperfridgeError::Io(message),
+            "parse" => HyperfridgeError::Parse(message),
+            "return_code" => HyperfridgeError::ReturnCode(message),
+            "digest_mismatch" => HyperfridgeError::DigestMismatch(message),
+            "signature_invalid" => HyperfridgeError::SignatureInvalid(message),
+            "decryption" => HyperfridgeError::Decryption(message),
+            "witness_invalid" => HyperfridgeError::WitnessInvalid(message),
+            "iban_not_found" => HyperfridgeError::IbanNotFound(message),
+            "binding_not_proven" => HyperfridgeError::BindingNotProven(message),
+            "inconsistent" => HyperfridgeError::Inconsistent(message),
+            "verification" => HyperfridgeError::Verification(message),
+            _ => HyperfridgeError::Proof(message),
+        }
+    }
+
+    /// name of the kind as in the message, e.g. `digest_mismatch`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            HyperfridgeError::Input(_) => "input",
+            HyperfridgeError::Io(_) => "io",
+            HyperfridgeError::Parse(_) => "parse",
+            HyperfridgeError::ReturnCode(_) => "return_code",
+            HyperfridgeError::DigestMismatch(_) => "digest_mismatch",
+            HyperfridgeError::SignatureInvalid(_) => "signature_invalid",
+            HyperfridgeError::Decryption(_) => "decryption",
+            HyperfridgeError::WitnessInvalid(_) => "witness_invalid",
+            HyperfridgeError::IbanNotFound(_) => "iban_not_found",
+            HyperfridgeError::BindingNotProven(_) => "binding_not_proven",
+            HyperfridgeError::Inconsistent(_) => "inconsistent",
+            HyperfridgeError::Proof(_) => "proof",
+            HyperfridgeError::Verification(_) => "verification",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            HyperfridgeError::Input(message)
+            | HyperfridgeError::Io(message)
+            | HyperfridgeError::Parse(message)
+            | HyperfridgeError::ReturnCode(message)
+            | HyperfridgeError::DigestMismatch(message)
+            | HyperfridgeError::SignatureInvalid(message)
+            | HyperfridgeError::Decryption(message)
+            | HyperfridgeError::WitnessInvalid(message)
+            | HyperfridgeError::IbanNotFound(message)
+            | HyperfridgeError::BindingNotProven(message)
+            | HyperfridgeError::Inconsistent(message)
+            | HyperfridgeError::Proof(message)
+            | HyperfridgeError::Verification(message) => message,
+        }
+    }
+
+    /// exit code of host and verifier, 1 is left for panics
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            HyperfridgeError::Input(_) => 2,
+            HyperfridgeError::Io(_) => 3,
+            HyperfridgeError::Parse(_) => 10,
+            HyperfridgeError::ReturnCode(_) => 11,
+            HyperfridgeError::DigestMismatch(_) => 12,
+            HyperfridgeError::SignatureInvalid(_) => 13,
+            HyperfridgeError::Decryption(_) => 14,
+            HyperfridgeError::WitnessInvalid(_) => 15,
+            HyperfridgeError::IbanNotFound(_) => 16,
+            HyperfridgeError::BindingNotProven(_) => 17,
+            HyperfridgeError::Inconsistent(_) => 18,
+            HyperfridgeError::Proof(_) => 20,
+            HyperfridgeError::Verification(_) => 21,
+        }
+    }
+
+    /// Recovers the error from the message of a failed prove, which contains the panic
+    /// message of the guest. The first `[kind] ` in the message counts, the guest message
+    /// itself may quote another one. Other failures of the prover are a `Proof` error.
+    pub fn from_guest(message: &str) -> HyperfridgeError {
+        let first = Self::KINDS
+            .iter()
+            .filter_map(|kind| {
+                let prefix = format!("[{}] ", kind);
+                message
+                    .find(&prefix)
+                    .map(|start| (start, kind, start + prefix.len()))
+            })
+            .min_by_key(|(start, _, _)| *start);
+        match first {
+            Some((_, kind, end)) => {
+                // the prover may add the location of the panic in the next line
+                let guest_message = message[end..].lines().next().unwrap_or_default().trim();
+                HyperfridgeError::new(kind, guest_message.to_string())
+            }
+            None => HyperfridgeError::Proof(message.trim().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for HyperfridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.kind(), self.message())
+    }
+}
+
+impl std::error::Error for HyperfridgeError {}
+
+impl From<EbicsError> for HyperfridgeError {
+    fn from(error: EbicsError) -> HyperfridgeError {
+        let message = error.to_string();
+        match error {
+            EbicsError::Response(_) | EbicsError::Camt53(_) => HyperfridgeError::Parse(message),
+            EbicsError::DigestMismatch { .. } => HyperfridgeError::DigestMismatch(message),
+            EbicsError::ReturnCode { .. } => HyperfridgeError::ReturnCode(message),
+            EbicsError::BankSignature(_) => HyperfridgeError::SignatureInvalid(message),
+            EbicsError::TransactionKey(_) | EbicsError::OrderData(_) => {
+                HyperfridgeError::Decryption(message)
+            }
+        }
+    }
+}
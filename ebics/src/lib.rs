@@ -0,0 +1,89 @@
+//! Verification of EBICS responses and parsing of the camt.053 statements in their order data,
+//! as used by the hyperfridge guest. The functions return a `Result` instead of panicking, so
+//! the same validation can run natively, e.g. in a backend before a response is proven:
+//!
+//! ```ignore
+//! let request = parse_ebics_response(authenticated, signed_info, signature_value, order_data)?;
+//! verify_bank_signature(&pub_bank, &request)?;
+//! let transaction_key = decrypt_transaction_key(&request, &client_key, &[])?;
+//! let files = decrypt_order_data(&request, &transaction_key)?;
+//! let document = parse_camt53(std::str::from_utf8(&files[1]).unwrap())?;
+//! ```
+//!
+//! `HyperfridgeError` are the errors of guest, host and verifier, an `EbicsError` converts
+//! into it.
+
+mod camt53;
+mod error;
+mod response;
+#[cfg(test)]
+mod test_ebics;
+
+use std::fmt;
+
+pub use camt53::{
+    parse_camt53, path_ends_with, Balance, BkTxCd, Camt53Version, Document, GrpHdr, Ntry, Stmt,
+    TxDtls, TxsSummry,
+};
+pub use error::HyperfridgeError;
+pub use response::{
+    check_return_code, decrypt_order_data, decrypt_transaction_key, get_client_key_hex,
+    order_data_hash, parse_ebics_response, verify_bank_signature, Request, EBICS_OK,
+};
+
+/// Errors of the verification steps of an EBICS response
+#[derive(Debug, Clone, PartialEq)]
+pub enum EbicsError {
+    /// malformed EbicsResponse XML or a required element is missing
+    Response(String),
+    /// the hash of the tags with authenticate=true does not match <DigestValue>
+    DigestMismatch {
+        expected: String,
+        calculated: String,
+    },
+    /// the bank did not mark the response as successful
+    ReturnCode {
+        location: String,
+        return_code: String,
+        description: String,
+    },
+    /// the signature of the bank over <SignedInfo> is invalid
+    BankSignature(String),
+    /// the transaction key could not be decrypted or does not match the response
+    TransactionKey(String),
+    /// the order data could not be decrypted, decompressed or unzipped
+    OrderData(String),
+    /// malformed or unsupported camt.053 document
+    Camt53(String),
+}
+
+impl fmt::Display for EbicsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EbicsError::DigestMismatch {
+                expected,
+                calculated,
+            } => write!(
+                f,
+                "hash of all c14n-ized tags with authenticate=true {} does not match the provided hash {}",
+                calculated, expected
+            ),
+            EbicsError::ReturnCode {
+                location,
+                return_code,
+                description,
+            } => write!(
+                f,
+                "EBICS {} return code {} ({}) - only responses with {} can be proven",
+                location, return_code, description, EBICS_OK
+            ),
+            EbicsError::Response(message)
+            | EbicsError::BankSignature(message)
+            | EbicsError::TransactionKey(message)
+            | EbicsError::OrderData(message)
+            | EbicsError::Camt53(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for EbicsError {}
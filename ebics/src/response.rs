@@ -0,0 +1,498 @@
+//! The EbicsResponse container: parsing of the c14n-ized tags, the signature of the bank,
+//! the transaction key and the encrypted, compressed order data.
+
+use crate::EbicsError;
+use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, KeyIvInit};
+use base64::{engine::general_purpose, Engine as _};
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+use rsa::traits::PublicKeyParts;
+use rsa::BigUint;
+use rsa::{Pkcs1v15Encrypt, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Read};
+use xmlparser::{ElementEnd, Token, Tokenizer};
+use zip::ZipArchive;
+
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+/// EBICS return code for a successful request
+pub const EBICS_OK: &str = "000000";
+
+/// Holds data retrieved from parsing the EbicsResponse XML file
+#[derive(Debug)]
+pub struct Request {
+    pub digest_value_b64: String,
+    pub autheticated_hashed: Vec<u8>,
+    pub bank_timestamp: String,
+    pub header_return_code: String,
+    pub body_return_code: String,
+    pub order_type: String,
+    pub transaction_key_b64: String,
+    pub signature_value_b64: String,
+    pub signed_info_hashed: Vec<u8>,
+    pub order_data_b64: String,
+}
+/// Holds data retrieved from parsing the EbicsResponse XML file
+#[allow(dead_code)]
+#[derive(Debug)]
+struct EbicsRequestData {
+    host_id: String,
+    timestamp: String,
+    order_type: String,
+    x002: String,
+    e002: String,
+    digest_value: String,
+    signature_value: String,
+}
+
+///
+/// Returns the digest value of a given public key - needs to match  with published hash
+///
+///
+/// <p>In Version “H003” of the EBICS protocol the ES of the financial:
+///
+/// <p>The SHA-256 hash values of the financial institution's public keys for X002 and E002 are
+/// composed by concatenating the exponent with a blank character and the modulus in hexadecimal
+/// representation (using lower case letters) without leading zero (as to the hexadecimal
+/// representation). The resulting string has to be converted into a byte array based on US ASCII
+/// code.
+///
+pub fn get_client_key_hex(pk: &RsaPublicKey) -> String {
+    let exponent = pk.e().to_bytes_be(); // Convert exponent to big-endian bytes
+    let modulus = pk.n().to_bytes_be(); // Convert modulus to big-endian bytes
+
+    // Convert bytes to lower case hexadecimal string
+    let exponent_hex = hex::encode(exponent).trim_start_matches('0').to_lowercase();
+    let modulus_hex = hex::encode(modulus).trim_start_matches('0').to_lowercase();
+
+    // Concatenate with a blank space
+    let combined = format!("{} {}", exponent_hex, modulus_hex);
+
+    // Convert to ASCII byte array
+    let ascii_bytes = combined.as_bytes();
+
+    // Compute SHA-256 hash and convert it to a hexadecimal string
+    hex::encode(Sha256::digest(ascii_bytes))
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc3275#section-3.1.2
+/// Signature Generation
+/// https://www.cfonb.org/fichiers/20130612170023_6_4_EBICS_Specification_2.5_final_2011_05_16_2012_07_01.pdf
+/// 5.5.1.2.1 Processing in the initialisation phase
+///
+///    1. Create SignedInfo element with SignatureMethod,
+///       CanonicalizationMethod and Reference(s).
+///    2. Canonicalize and then calculate the SignatureValue over SignedInfo
+///       based on algorithms specified in SignedInfo.
+///       3. Construct the Signature element that includes SignedInfo,
+///       Object(s) (if desired, encoding may be different than that used
+///       for signing), KeyInfo (if required), and SignatureValue.
+///
+///    Note, if the Signature includes same-document references, [XML] or
+///    [XML-schema] validation of the document might introduce changes that
+///    break the signature.  Consequently, applications should be careful to
+///    consistently process the document or refrain from using external
+///    contributions (e.g., defaults and entities).
+///
+/// Signature Validation
+///
+///    1. Obtain the keying information from KeyInfo or from an external
+///       source.
+///    2. Obtain the canonical form of the SignatureMethod using the
+///       CanonicalizationMethod and use the result (and previously obtained
+///       KeyInfo) to confirm the SignatureValue over the SignedInfo
+///       element.
+///
+///    Note, KeyInfo (or some transformed version thereof) may be signed via
+///    a Reference element.  Transformation and validation of this reference
+///    (3.2.1) is orthogonal to Signature Validation which uses the KeyInfo
+///    as parsed.
+///
+///    Additionally, the SignatureMethod URI may have been altered by the
+///    canonicalization of SignedInfo (e.g., absolutization of relative
+///    URIs) and it is the canonical form that MUST be used.  However, the
+///    required canonicalization [XML-C14N] of this specification does not
+///    change URIs.
+pub fn verify_bank_signature(pub_bank: &RsaPublicKey, request: &Request) -> Result<(), EbicsError> {
+    // Decode the signature
+    let signature_value_bytes = general_purpose::STANDARD
+        .decode(&request.signature_value_b64)
+        .map_err(|e| EbicsError::BankSignature(format!("invalid base64 SignatureValue {}", e)))?;
+
+    // Create a signer with PKCS#1 v1.5 padding - from the standard:
+    //     2.3.2 RSA-SHA256
+    //    Identifier:
+    //         http://www.w3.org/2001/04/xmldsig-more#rsa-sha256
+
+    //    This implies the PKCS#1 v1.5 padding algorithm [RFC3447] as described
+    //    in section 2.3.1 but with the ASN.1 BER SHA-256 algorithm designator
+    //    prefix.
+
+    let scheme = Pkcs1v15Sign::new::<Sha256>();
+
+    // Verify the signature
+    pub_bank
+        .verify(scheme, &request.signed_info_hashed, &signature_value_bytes)
+        .map_err(|e| {
+            EbicsError::BankSignature(format!("bank Signature could not be verified {}", e))
+        })
+}
+
+/// Parse the EbicsResponse XML file, return a structure. Note that the EbicsResponse XML file is a container for the
+/// actual payload, which is a ZIP file containing the daily statements and account data - also in XML.
+/// See  https://www.cfonb.org/fichiers/20130612170023_6_4_EBICS_Specification_2.5_final_2011_05_16_2012_07_01.pdf
+/// Chapter 5.6.1.1.2
+/// Check out example of real file in /data/response_template_pretty.xml - it is a bit long to be included here.
+pub fn parse_ebics_response(
+    authenticated_xml_c14n: &str,
+    signed_info_xml_c14n: &str,
+    signature_value_xml: &str,
+    order_data_xml: &str,
+) -> Result<Request, EbicsError> {
+    // parse the XML file, validate, return a structure (documents
+    let mut curr_tag: &str = "";
+
+    let mut digest_value_b64: String = String::new();
+    let mut signature_value_b64: String = String::new();
+    let mut bank_timestamp: String = String::new();
+    let mut transaction_key_b64: String = String::new();
+    let mut order_data_b64: String = String::new();
+    let mut header_return_code: String = String::new();
+    let mut body_return_code: String = String::new();
+    let mut order_type: String = String::new();
+    let mut in_header = false;
+
+    // digest over all tags with authenticated=true; later check it with digest_value_b64
+    let authenticated_hashed = Sha256::digest(authenticated_xml_c14n.as_bytes()).to_vec();
+    let calculated_digest_b64 = general_purpose::STANDARD.encode(&authenticated_hashed);
+    let signed_info_hashed: Vec<u8> = Sha256::digest(signed_info_xml_c14n.as_bytes()).to_vec();
+    //let tokens=Tokenizer::from(xml_data); // use from_fragment so deactive xml checks
+
+    // reconstruct document from the XML file snippets to parse content in one go
+    let all_tags = format!(
+        "{}{}{}{}",
+        authenticated_xml_c14n, signed_info_xml_c14n, signature_value_xml, order_data_xml,
+    );
+    let tokens = Tokenizer::from_fragment(&all_tags, 0..all_tags.len());
+
+    // only values from the authenticated tags are covered by the signature of the bank
+    let authenticated_end = authenticated_xml_c14n.len();
+
+    // Parse XML and build data structure.
+    // To better understand the XML parsing, look an a an exmaple of the XML file,
+    // e.g. in  /data/response_template_pretty.xml
+    for token in tokens {
+        match token {
+            Ok(Token::ElementStart { local, .. }) => {
+                //print_verbose!("   open tag  as_str {:?}", local.as_str());
+                curr_tag = local.as_str();
+                if curr_tag == "header" {
+                    in_header = true;
+                }
+            }
+            Ok(Token::ElementEnd { end, .. }) => {
+                if let ElementEnd::Close(.., local) = end {
+                    // print_verbose!("   close tag  as_str {:?}", local.as_str());
+                    // handling Close variant
+                    curr_tag = "";
+                    if local.as_str() == "header" {
+                        in_header = false;
+                    }
+                }
+            }
+
+            //  <SegmentNumber lastSegment="true">1</SegmentNumber> needs to be found
+            Ok(Token::Attribute { local, value, .. }) if (curr_tag == "SegmentNumber") => {
+                if !(local == "lastSegment" && value == "true") {
+                    return Err(EbicsError::Response("not the last segment".to_string()));
+                };
+            }
+
+            Ok(Token::Text { text }) if curr_tag == "SegmentNumber" => {
+                if !(text == "1") {
+                    return Err(EbicsError::Response(
+                        "only one segment implemented".to_string(),
+                    ));
+                };
+            }
+            //  <ds:DigestValue>qcP1kr+olKNTe23cugTwL+76sZEmD7nMQT6SjZwOlyg=</ds:DigestValue>
+            Ok(Token::Text { text }) if curr_tag == "DigestValue" => {
+                digest_value_b64 = text.to_string();
+                // As the XML standard for c14n does not remove blanks between tags, you need to check
+                // exactly the same character string which has been used to generate the hash, which is
+                // usually available in the direct response of the banking backend.
+                if digest_value_b64 != calculated_digest_b64 {
+                    return Err(EbicsError::DigestMismatch {
+                        expected: digest_value_b64,
+                        calculated: calculated_digest_b64,
+                    });
+                }
+            }
+            // <ds:SignatureValue>WW6VtstkLq+c8YKP6a1i6AijJlAAPEm9WChBDSjKU7zUI3DxKUvRPEGoNpPlJk....zxvIpJSZTSh920UAwZUFy3pmJzZC9AGieIALQ==</ds:SignatureValue>
+            Ok(Token::Text { text }) if curr_tag == "SignatureValue" => {
+                signature_value_b64 = text.to_string();
+            }
+            // <TransactionKey>XTKNSQh2cXKEM4WR/t4fMrl2QnD1YhO6IVDg8ZHz+81rwwd88NNZFr8T6wU8lHs5bj....Z32QDsom6zzEMyedKePYbxxxpAAk0RWhPQG/ZTw==</TransactionKey>
+            Ok(Token::Text { text }) if curr_tag == "TransactionKey" => {
+                transaction_key_b64 = text.to_string();
+            }
+            // <TimestampBankParameter authenticate="true">2023-11-25T06:00:54.7545059Z</TimestampBankParameter>
+            Ok(Token::Text { text })
+                if curr_tag == "TimestampBankParameter" && text.start() < authenticated_end =>
+            {
+                bank_timestamp = text.to_string();
+            }
+            // <header authenticate="true"> ... <mutable> <ReturnCode>000000</ReturnCode> is the technical return code,
+            // <ReturnCode authenticate="true">000000</ReturnCode> in the body the business related return code
+            Ok(Token::Text { text })
+                if curr_tag == "ReturnCode" && text.start() < authenticated_end =>
+            {
+                if in_header {
+                    header_return_code = text.to_string();
+                } else {
+                    body_return_code = text.to_string();
+                }
+            }
+            // <OrderDetails><OrderType>C53</OrderType> - only part of the response for some banks
+            Ok(Token::Text { text })
+                if (curr_tag == "OrderType" || curr_tag == "AdminOrderType")
+                    && text.start() < authenticated_end =>
+            {
+                order_type = text.to_string();
+            }
+            Ok(Token::Text { text }) if curr_tag == "OrderData" => {
+                order_data_b64 = text.to_string();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                return Err(EbicsError::Response(format!(
+                    "error parsing ebics response {}",
+                    e
+                )))
+            }
+        }
+    }
+
+    let required = [
+        (&digest_value_b64, "no digest value in EbicsResponse XML"),
+        (
+            &transaction_key_b64,
+            "no transaction key in EbicsResponse XML",
+        ),
+        (
+            &signature_value_b64,
+            "no signature value in EbicsResponse XML",
+        ),
+        (&order_data_b64, "no order data value in EbicsResponse XML"),
+    ];
+    if let Some((_, message)) = required.iter().find(|(value, _)| value.is_empty()) {
+        return Err(EbicsError::Response(message.to_string()));
+    }
+
+    // we only prove responses the bank marked as successful
+    check_return_code("header", &header_return_code)?;
+    check_return_code("body", &body_return_code)?;
+
+    Ok(Request {
+        digest_value_b64,
+        autheticated_hashed: authenticated_hashed,
+        transaction_key_b64,
+        bank_timestamp,
+        header_return_code,
+        body_return_code,
+        order_type,
+        signature_value_b64,
+        signed_info_hashed,
+        order_data_b64,
+    })
+}
+
+/// EBICS return codes are 6 digit strings, `000000` (EBICS_OK) is the only one we accept.
+/// See EBICS specification, Appendix: Return Codes.
+pub fn check_return_code(location: &str, return_code: &str) -> Result<(), EbicsError> {
+    let description = match return_code {
+        EBICS_OK => return Ok(()),
+        "" => "missing",
+        "011000" => "EBICS_DOWNLOAD_POSTPROCESS_DONE",
+        "011001" => "EBICS_DOWNLOAD_POSTPROCESS_SKIPPED",
+        "061001" => "EBICS_AUTHENTICATION_FAILED",
+        "061002" => "EBICS_INVALID_REQUEST",
+        "090003" => "EBICS_AUTHORISATION_ORDER_TYPE_FAILED",
+        "090004" => "EBICS_INVALID_ORDER_DATA_FORMAT",
+        "090005" => "EBICS_NO_DOWNLOAD_DATA_AVAILABLE",
+        "091002" => "EBICS_INVALID_USER_OR_USER_STATE",
+        "091005" => "EBICS_INVALID_ORDER_TYPE",
+        _ => "not OK",
+    };
+    Err(EbicsError::ReturnCode {
+        location: location.to_string(),
+        return_code: return_code.to_string(),
+        description: description.to_string(),
+    })
+}
+
+/// The Transaction key is transmitted as base64.
+/// The used this transaction key to encrypt the payload,
+/// and is integrated in the Ebics Response file encrypted with the
+/// public key of the client, which is exchanged when setting up the
+/// Ebics connection between bank and client (see HIA and INI requests)
+///
+/// See  https://www.cfonb.org/fichiers/20130612170023_6_4_EBICS_Specification_2.5_final_2011_05_16_2012_07_01.pdf
+/// Chapter 6.2 and 11.3.2
+/// The order data and ES’s of an EBICS transaction are symmetrically encrypted. For each
+/// EBICS transaction, a random symmetrical key (transaction key) is generated by the sender
+/// of order data and/or ES’s that is used for encryption of both the order data and the ES’s. The
+/// symmetrical key is transmitted to the recipient asymmetrically-encoded.
+/// Generation of the transaction key (see Appendix, Chapter 15)
+/// -AES-128 (key length 128 bit) in CBC mode
+/// -ICV (Initial Chaining Value) = 0
+/// -Padding process in accordance with ANSI X9.23 / ISO 10126-2.
+///
+/// Encryption of the messages
+/// Padding of the message:
+/// The method Padding with Octets in accordance with ANSI X9.23 is used for padding the
+/// message, i.e. in all cases, data is appended to the message that is to be encrypted.
+/// Application of the encryption algorithm:
+/// The message is encrypted in CBC mode in accordance with ANSI X3.106 with the secret key
+/// DEK according to the 2-key triple DES process as specified in ANSI X3.92-1981.
+/// In doing this, the following initialisation value “ICV” is used: X ‘00 00 00 00 00 00 00 00’.
+///
+/// As RSA decryption is expensive in the zkVM, the decrypted (still padded) transaction key
+/// can be given as `decrypted_tx_key`: it is encrypted with the public client key and must
+/// match the encrypted transaction key of the response.
+pub fn decrypt_transaction_key(
+    request: &Request,
+    client_key: &RsaPrivateKey,
+    decrypted_tx_key: &[u8],
+) -> Result<Vec<u8>, EbicsError> {
+    let transaction_key_bin = general_purpose::STANDARD
+        .decode(&request.transaction_key_b64)
+        .map_err(|e| EbicsError::TransactionKey(format!("invalid base64 TransactionKey {}", e)))?;
+
+    if !decrypted_tx_key.is_empty() {
+        // Do some check on the provided key - ensure that the data is long enough and has the correct PKCS#1 v1.5 padding prefix.
+        if decrypted_tx_key.len() < 3 {
+            return Err(EbicsError::TransactionKey(
+                "Invalid data: Too short to contain PKCS#1 v1.5 padding".to_string(),
+            ));
+        }
+        if decrypted_tx_key[0] != 0x00 || decrypted_tx_key[1] != 0x02 {
+            return Err(EbicsError::TransactionKey(
+                "Invalid data: Missing 0x00 0x02 prefix of PKCS#1 v1.5 padding".to_string(),
+            ));
+        }
+
+        // most important - check if the recreated, encrypted tx key equalx to the one provided by the XML file
+        let pub_key = RsaPublicKey::from(client_key);
+        // https://docs.rs/rsa/latest/rsa/hazmat/fn.rsa_encrypt.html
+        // Raw RSA encryption and "hazmat" is considered "OK", because do do not use the encryption.
+        // We check if if provided decrypted key was using the decrypted key in the XML as source.
+        let encrypted_recreated =
+            rsa::hazmat::rsa_encrypt(&pub_key, &BigUint::from_bytes_be(decrypted_tx_key))
+                .map_err(|e| EbicsError::TransactionKey(e.to_string()))?;
+        if BigUint::from_bytes_be(&transaction_key_bin) != encrypted_recreated {
+            return Err(EbicsError::TransactionKey(
+                "the provided decrypted transaction key does not math the one provided by the XML file"
+                    .to_string(),
+            ));
+        }
+
+        // lets return the decrypted tx key from the provided one - so we do not have to to the expensive RSA.decrypt.
+        // remove the padding, return the decrypted key
+
+        // look for first 00 which marks the end of the padding - but be aware that the padding always starts with 0002
+        return match decrypted_tx_key
+            .iter()
+            .skip(4)
+            .position(|&x| x == 0x00)
+            .map(|p| p + 1)
+        {
+            Some(padding_end) if padding_end > 2 => {
+                Ok(decrypted_tx_key[(padding_end + 4)..].to_vec())
+            }
+            _ => Err(EbicsError::TransactionKey(
+                "Invalid data: Padding format incorrect or missing".to_string(),
+            )),
+        };
+    }
+
+    // Decrypt with PKCS1 padding
+    client_key
+        .decrypt(Pkcs1v15Encrypt, &transaction_key_bin)
+        .map_err(|e| {
+            EbicsError::TransactionKey(format!(
+                "transaction key to decrypt payload could __NOT__ be decrypted {}",
+                e
+            ))
+        })
+}
+
+/// sha256 hash of the order data according to Ebics Standard, this is what the witnesses sign
+pub fn order_data_hash(request: &Request) -> Result<Vec<u8>, EbicsError> {
+    let order_data_bin = general_purpose::STANDARD
+        .decode(&request.order_data_b64)
+        .map_err(|e| EbicsError::OrderData(format!("invalid base64 OrderData {}", e)))?;
+    Ok(Sha256::digest(order_data_bin).to_vec())
+}
+
+/// using the decrypted transaction key, lets decrypt the payload.
+/// The payload is considered a stream which is compressed with the deflate alogrithm.
+/// The stream is actually a ZIP file, which containts the XML documents which hold the
+/// daily statements and account data.
+///
+/// Result is a vector where each odd index is a filename, even index is the files conent,
+/// both as Vec(u8)
+pub fn decrypt_order_data(
+    request: &Request,
+    transaction_key_bin: &[u8],
+) -> Result<Vec<Vec<u8>>, EbicsError> {
+    // extract the order data from the request - it is base64 encoded
+    let order_data_bin = general_purpose::STANDARD
+        .decode(&request.order_data_b64)
+        .map_err(|e| EbicsError::OrderData(format!("invalid base64 OrderData {}", e)))?;
+
+    // does the following:
+    // openssl enc -d -aes-128-cbc -nopad -in orderdata_decoded.bin -out $decrypted_file -K ${transaction_key_hex} -iv 00000000000000000000000000000000
+    let iv: [u8; 16] = [0; 16];
+
+    // https://docs.rs/crate/stegosaurust/latest/source/src/crypto.rs
+    // Create an AES-128-CBC cipher instance
+    let pt = Aes128CbcDec::new_from_slices(transaction_key_bin, &iv)
+        .map_err(|e| EbicsError::OrderData(format!("invalid transaction key {}", e)))?;
+
+    // http://www.ietf.org/rfc/rfc1950.txt http://www.ietf.org/rfc/rfc1951.txt
+    let mut result_bytes = vec![0u8; order_data_bin.len()]; // Output buffer with the same size as input
+                                                            // do the decryption, but still the result is compressed
+    let decrypted_data = pt
+        .decrypt_padded_b2b_mut::<NoPadding>(&order_data_bin, &mut result_bytes)
+        .map_err(|e| EbicsError::OrderData(format!("Failed to decrypt {}", e)))?;
+
+    // uncompressed the data
+    let decompressed = decompress_to_vec_zlib(decrypted_data)
+        .map_err(|e| EbicsError::OrderData(format!("Failed to decompress! {:?}", e)))?;
+    let cursor = Cursor::new(decompressed);
+    let mut archive = ZipArchive::new(cursor)
+        .map_err(|e| EbicsError::OrderData(format!("Failed to read ZIP archive {}", e)))?;
+
+    // Extract the contents of the ZIP archive
+    // We may have several documents in the ZIP file, so we return a vector of Vec<u8>
+    let mut file_contents: Vec<Vec<u8>> = Vec::new();
+    for i in 0..archive.len() {
+        // get the filename of what we are about to extract
+        let mut file = archive.by_index(i).map_err(|e| {
+            EbicsError::OrderData(format!("Failed to read file in ZIP archive {}", e))
+        })?;
+
+        // Convert filename to Vec<u8> and push it to file_contents
+        let filename = file.name().to_string().into_bytes();
+        file_contents.push(filename);
+
+        // Read file contents into Vec<u8> and push it to file_contents
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)
+            .map_err(|e| EbicsError::OrderData(format!("Failed to read file content {}", e)))?;
+        file_contents.push(contents);
+    }
+
+    Ok(file_contents)
+}
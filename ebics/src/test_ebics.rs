@@ -0,0 +1,250 @@
+use super::*;
+use base64::{engine::general_purpose, Engine as _};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
+
+const BANK_PUBLIC_KEY_X002_PEM: &str = include_str!("../../data/pub_bank.pem");
+const USER_PRIVATE_KEY_E002_PEM: &str = include_str!("../../data/client.pem");
+
+const TX_KEY_DECRYPTED: &[u8] =
+    include_bytes!("../../data/test/test.xml-TransactionKeyDecrypt.bin");
+
+macro_rules! include_resource {
+    ($file:expr) => {
+        include_str!(concat!("../../data/test/test.xml-", $file))
+    };
+}
+const SIGNED_INFO_XML_C14N: &str = include_resource!("SignedInfo");
+const AUTHENTICATED_XML_C14N: &str = include_resource!("authenticated");
+const SIGNATURE_VALUE_XML: &str = include_resource!("SignatureValue");
+const ORDER_DATA_XML: &str = include_resource!("OrderData");
+
+fn test_request() -> Request {
+    parse_ebics_response(
+        AUTHENTICATED_XML_C14N,
+        SIGNED_INFO_XML_C14N,
+        SIGNATURE_VALUE_XML,
+        ORDER_DATA_XML,
+    )
+    .expect("test response is valid")
+}
+
+/// asserts the decrypted order data is a list of filename, XML content
+fn check_files(files: &[Vec<u8>]) {
+    assert!(!files.is_empty());
+    for (index, item) in files.iter().enumerate() {
+        if index % 2 == 0 {
+            let filename = String::from_utf8(item.clone())
+                .expect("Failed to convert filename bytes to string");
+            assert!(
+                filename.ends_with(".xml"),
+                "Filename does not end with .xml"
+            );
+        } else {
+            assert_eq!(
+                &item[0..5],
+                b"<?xml",
+                "File content does not start with <xml>"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_digest() {
+    //The content of the DigestValue element shall be the base64
+    //encoding of the SHA-256 digest of the authenticated tags.
+    let request = test_request();
+    let sha = Sha256::digest(AUTHENTICATED_XML_C14N.as_bytes());
+    assert_eq!(
+        request.digest_value_b64,
+        general_purpose::STANDARD.encode(sha)
+    );
+}
+
+#[test]
+fn test_digest_mismatch() {
+    // a blank between tags changes the c14n hash
+    let tampered = format!("{} ", AUTHENTICATED_XML_C14N);
+    let res = parse_ebics_response(
+        &tampered,
+        SIGNED_INFO_XML_C14N,
+        SIGNATURE_VALUE_XML,
+        ORDER_DATA_XML,
+    );
+    assert!(matches!(res, Err(EbicsError::DigestMismatch { .. })));
+}
+
+#[test]
+fn test_validate_signature() {
+    let bank_public_key = RsaPublicKey::from_public_key_pem(BANK_PUBLIC_KEY_X002_PEM)
+        .expect("Failed to create bank public key");
+    verify_bank_signature(&bank_public_key, &test_request()).unwrap();
+}
+
+#[test]
+fn test_invalid_signature() {
+    let bank_public_key = RsaPublicKey::from_public_key_pem(BANK_PUBLIC_KEY_X002_PEM).unwrap();
+    let mut request = test_request();
+    request.signature_value_b64 = general_purpose::STANDARD.encode([0u8; 256]);
+    assert!(matches!(
+        verify_bank_signature(&bank_public_key, &request),
+        Err(EbicsError::BankSignature(_))
+    ));
+}
+
+#[test]
+fn test_decrypt_txkey() {
+    let request = test_request();
+    let private_key = RsaPrivateKey::from_pkcs8_pem(USER_PRIVATE_KEY_E002_PEM).unwrap();
+    let transaction_key_bin = decrypt_transaction_key(&request, &private_key, &[]).unwrap();
+    assert_eq!(transaction_key_bin.len(), 16);
+    check_files(&decrypt_order_data(&request, &transaction_key_bin).unwrap());
+}
+
+#[test]
+fn test_decrypt_txkey_reverse() {
+    let request = test_request();
+    let private_key = RsaPrivateKey::from_pkcs8_pem(USER_PRIVATE_KEY_E002_PEM).unwrap();
+    let transaction_key_bin =
+        decrypt_transaction_key(&request, &private_key, TX_KEY_DECRYPTED).unwrap();
+    assert_eq!(transaction_key_bin.len(), 16);
+    check_files(&decrypt_order_data(&request, &transaction_key_bin).unwrap());
+}
+
+#[test]
+fn test_wrong_transaction_key() {
+    let request = test_request();
+    assert!(matches!(
+        decrypt_order_data(&request, &[0u8; 16]),
+        Err(EbicsError::OrderData(_))
+    ));
+}
+
+#[test]
+fn test_order_data_hash() {
+    let order_data_bin = general_purpose::STANDARD
+        .decode(&test_request().order_data_b64)
+        .unwrap();
+    assert_eq!(
+        order_data_hash(&test_request()).unwrap(),
+        Sha256::digest(order_data_bin).to_vec()
+    );
+}
+
+#[test]
+fn test_parse_chain() {
+    let bank_public_key = RsaPublicKey::from_public_key_pem(BANK_PUBLIC_KEY_X002_PEM).unwrap();
+    let private_key = RsaPrivateKey::from_pkcs8_pem(USER_PRIVATE_KEY_E002_PEM).unwrap();
+    let request = test_request();
+    verify_bank_signature(&bank_public_key, &request).unwrap();
+    let transaction_key = decrypt_transaction_key(&request, &private_key, &[]).unwrap();
+    let files = decrypt_order_data(&request, &transaction_key).unwrap();
+    for content in files.iter().skip(1).step_by(2) {
+        let document = parse_camt53(std::str::from_utf8(content).unwrap()).unwrap();
+        assert!(!document.stmts.is_empty());
+        assert!(!document.stmts[0].iban.is_empty());
+    }
+}
+
+#[test]
+fn test_return_codes() {
+    let request = test_request();
+    assert_eq!(request.header_return_code, EBICS_OK);
+    assert_eq!(request.body_return_code, EBICS_OK);
+    assert_eq!(request.bank_timestamp, "2023-11-30T08:38:11.8835379Z");
+    // the test response does not contain the order type
+    assert_eq!(request.order_type, "");
+}
+
+#[test]
+fn test_return_code_no_data() {
+    let err = check_return_code("body", "090005").unwrap_err();
+    assert!(matches!(err, EbicsError::ReturnCode { .. }));
+    assert!(err.to_string().contains("EBICS_NO_DOWNLOAD_DATA_AVAILABLE"));
+}
+
+#[test]
+fn test_return_code_missing() {
+    let err = check_return_code("header", "").unwrap_err();
+    assert!(err.to_string().contains("return code  (missing)"));
+}
+
+#[test]
+fn test_camt53_unknown_version() {
+    let err = parse_camt53(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.03\"></Document>",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("unsupported camt.053 version"));
+}
+
+#[test]
+fn test_camt53_without_namespace() {
+    let err = parse_camt53("<Document><BkToCstmrStmt></BkToCstmrStmt></Document>").unwrap_err();
+    assert!(matches!(err, EbicsError::Camt53(_)));
+    assert!(err.to_string().contains("without camt.053 namespace"));
+}
+
+#[test]
+fn test_camt53_prefixed_namespace() {
+    let document = parse_camt53(concat!(
+        "<ns:Document xmlns:ns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.08\" ",
+        "xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\"><ns:BkToCstmrStmt><ns:Stmt>",
+        "<ns:Acct><ns:Id><ns:IBAN>CH4308307000289537312</ns:IBAN></ns:Id></ns:Acct>",
+        "</ns:Stmt></ns:BkToCstmrStmt></ns:Document>"
+    ))
+    .unwrap();
+    assert_eq!(document.msg_version, "camt.053.001.08");
+    assert_eq!(document.stmts[0].iban, "CH4308307000289537312");
+}
+
+#[test]
+fn test_camt53_other_prefix_ignored() {
+    // only the namespace of the prefix of <Document> counts
+    let err = parse_camt53(concat!(
+        "<ns:Document xmlns:ns=\"urn:iso:std:iso:20022:tech:xsd:camt.054.001.08\" ",
+        "xmlns:other=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.08\"></ns:Document>"
+    ))
+    .unwrap_err();
+    assert!(err.to_string().contains("unsupported camt.053 version"));
+}
+
+#[test]
+fn test_error_from_ebics() {
+    let err = HyperfridgeError::from(check_return_code("body", "090005").unwrap_err());
+    assert_eq!(err.kind(), "return_code");
+    assert_eq!(err.exit_code(), 11);
+    let tampered = format!("{} ", AUTHENTICATED_XML_C14N);
+    let err: HyperfridgeError = parse_ebics_response(
+        &tampered,
+        SIGNED_INFO_XML_C14N,
+        SIGNATURE_VALUE_XML,
+        ORDER_DATA_XML,
+    )
+    .unwrap_err()
+    .into();
+    assert!(matches!(err, HyperfridgeError::DigestMismatch(_)));
+}
+
+#[test]
+fn test_error_from_guest() {
+    let guest = HyperfridgeError::WitnessInvalid(
+        "1 of 2 required witness signatures are valid".to_string(),
+    );
+    // as returned by the prover for a panic in the guest
+    let message = format!("Guest panicked: {}\nlocation: src/lib.rs:1290:9", guest);
+    assert_eq!(HyperfridgeError::from_guest(&message), guest);
+    assert_eq!(HyperfridgeError::from_guest(&message).exit_code(), 15);
+    // the guest message quotes an input which looks like another kind
+    let quoted = "Guest panicked: [parse] invalid amount '[input] 1'\nlocation: src/lib.rs:1:1";
+    assert_eq!(
+        HyperfridgeError::from_guest(quoted),
+        HyperfridgeError::Parse("invalid amount '[input] 1'".to_string())
+    );
+    assert_eq!(
+        HyperfridgeError::from_guest("out of memory"),
+        HyperfridgeError::Proof("out of memory".to_string())
+    );
+}
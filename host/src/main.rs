@@ -1,8 +1,13 @@
 use chrono::Local;
 use clap::{Parser, Subcommand};
+use hyperfridge_ebics::HyperfridgeError;
 // These constants represent the RISC-V ELF and the image ID generated by risc0-build.
 // The ELF is used for proving and the ID is used for verification.
-use methods::{HYPERFRIDGE_ELF, HYPERFRIDGE_ID};
+// Each proof type has its own guest image, see GUEST_IMAGES.
+use methods::{
+    AGGREGATES_ELF, AGGREGATES_ID, BALANCE_ELF, BALANCE_ID, BINDING_ELF, BINDING_ID,
+    HYPERFRIDGE_ELF, HYPERFRIDGE_ID,
+};
 use pem::parse;
 use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
 use rsa::pkcs8::DecodePublicKey;
@@ -25,28 +30,199 @@ macro_rules! print_verbose {
     };
 }
 
-/// Holds the commitment data which is publicly visible in the proof
-#[derive(Deserialize, Debug)]
+/// Holds the commitment data which is publicly visible in the proof - fields omitted by the
+/// disclosure policy are left empty
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
 #[allow(dead_code)]
 struct Commitment {
     hostinfo: String,
-    iban: String,
+    proof: String,
+    policy_hash: String,
+    challenge: String,
+    bind_to: String,
+    bind_checked: bool,
+    include_pending: bool,
+    tx_codes: Vec<String>,
+    order_type: String,
+    bank_timestamp: String,
+    accounts: Vec<String>,
     pub_bank_pem: String,
-    pub_witness_pem: String,
+    pub_witness_pems: Vec<String>,
+    witness_schemes: Vec<String>,
+    witness_keys_hash: String,
+    witness_threshold: u32,
     pub_client_pem: String,
     stmts: Vec<Stmt>,
+    payments: Vec<Payment>,
+    reserves: Option<Reserves>,
+    fx: Option<FxTotal>,
+}
+
+/// Struct for the total of the closing balances in the reporting currency
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct FxTotal {
+    reporting_ccy: String,
+    ccy_exp: u32,
+    total_minor: i64,
+    accounts: usize,
+    rate_date: String,
+    rates_hash: String,
+    fx_key_fingerprint: String,
+}
+
+/// Struct for the proof of reserves: totals of the closing balances up to a date per currency
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct Reserves {
+    date: String,
+    oldest_dt: String,
+    banks: Vec<ReservesBank>,
+    totals: Vec<ReservesTotal>,
+    liabilities: Vec<Liability>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct ReservesBank {
+    pub_bank_fingerprint: String,
+    bank_timestamp: String,
+    order_type: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct ReservesTotal {
+    ccy: String,
+    ccy_exp: u32,
+    total_minor: i64,
+    accounts: usize,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct Liability {
+    ccy: String,
+    amt_minor: i64,
+    covered: bool,
+}
+
+/// A further EBICS response for the proof of reserves, in the order the guest reads it:
+/// SignedInfo, authenticated, SignatureValue, OrderData, modulus and exponent of the bank key,
+/// client key, decrypted transaction key and witness signatures
+type ResponseInput = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    Vec<u8>,
+    Vec<String>,
+);
+
+/// Further EBICS responses with their keys, the date and the liabilities of the proof of reserves
+#[derive(Debug, Default)]
+struct ReservesInput {
+    requests: Vec<String>,
+    bankkeys: Vec<String>,
+    clientkeys: Vec<String>,
+    date: String,
+    liabilities: Vec<String>,
+}
+
+/// Reporting currency and the FX rates signed by the rate provider, as passed to the guest
+#[derive(Debug, Default)]
+struct FxInput {
+    reporting_ccy: String,
+    rates: String,
+    signature_hex: String,
+    pub_key_pem: String,
+    scheme: String,
 }
 
 /// Struct for the statement data in the commitment
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
 #[allow(dead_code)]
 struct Stmt {
+    msg_version: String,
+    iban: String,
+    othr_id: String,
+    othr_schme_nm: String,
+    acct_ccy: String,
     elctrnc_seq_nb: String,
     fr_dt_tm: String,
     to_dt_tm: String,
-    amt: String,
+    balances: Vec<Balance>,
+    ntries: EntrySummary,
+    txs_summry: EntryTotals,
+}
+
+/// Struct for the totals of all entries of a statement, checked against <TxsSummry>
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct EntryTotals {
+    nb_of_ntries: usize,
+    sum_minor: i64,
+    cdt_nb_of_ntries: usize,
+    cdt_sum_minor: i64,
+    dbt_nb_of_ntries: usize,
+    dbt_sum_minor: i64,
+    checked: bool,
+}
+
+/// Struct for the entries of a statement in the commitment - counts per category and
+/// the sum of booked (and optionally pending) entries in minor units
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct EntrySummary {
+    booked: usize,
+    pending: usize,
+    info: usize,
+    reversed: usize,
+    txs: usize,
     ccy: String,
+    inflow_minor: i64,
+    outflow_minor: i64,
+}
+
+/// Struct for the status of an uploaded payment (pain.002) in the commitment
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct Payment {
+    msg_id: String,
+    orgnl_msg_id: String,
+    pmt_inf_id: String,
+    nb_of_txs: usize,
+    ctrl_sum: String,
+    amt_minor: i64,
+    ccy: String,
+    sts: String,
+}
+
+/// Struct for the balances of a statement in the commitment
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct Balance {
     cd: String,
+    amt: String,
+    amt_minor: i64,
+    ccy: String,
+    ccy_exp: u32,
+    cdt_dbt_ind: String,
+    dt: String,
 }
 
 fn main() {
@@ -61,8 +237,18 @@ fn main() {
     // structure holding the command line arguments
     let pub_bank_pem_filename: String;
     let client_pem_filename: String;
-    let pub_witness_pem_filename: String;
-    let iban: String;
+    let pub_witness_pem_filenames: Vec<String>;
+    let witness_schemes: Vec<String>;
+    let witness_threshold: u32;
+    let challenge: String;
+    let bind_to: String;
+    let bind_check: bool;
+    let include_pending: bool;
+    let tx_codes: Vec<String>;
+    let policy: String;
+    let fx: FxInput;
+    let reserves: ReservesInput;
+    let accounts: Vec<String>;
     let camt53_filename: String;
 
     // use cli framework to parse command line arguments
@@ -73,9 +259,31 @@ fn main() {
             bankkey,
             clientkey,
             witnesskey,
+            witness_scheme,
+            witness_threshold: threshold,
 
             clientiban,
+            clientaccount,
             request,
+            challenge: verifier_challenge,
+            bind_to: binding,
+            bind_check: check_binding,
+            include_pending: pending,
+            tx_code,
+            policy: policy_file,
+            hash_iban,
+            fingerprint_keys,
+            salt,
+            reporting_ccy,
+            fx_rates,
+            fx_signature,
+            fx_key,
+            fx_scheme,
+            reserves_date,
+            liability,
+            reserves_request,
+            reserves_bankkey,
+            reserves_clientkey,
         }) => {
             // convert cli arguments for later usage
             pub_bank_pem_filename = (*bankkey
@@ -96,16 +304,112 @@ fn main() {
             .unwrap()
             .to_string();
 
-            pub_witness_pem_filename = (*witnesskey
-                .as_ref()
-                .expect("extracting path for file")
-                .clone()
-                .into_os_string())
-            .to_str()
-            .unwrap()
-            .to_string();
+            pub_witness_pem_filenames = witnesskey
+                .iter()
+                .map(|path| path.to_str().unwrap().to_string())
+                .collect();
+            witness_threshold = *threshold;
+            // one scheme for all witnesses or one scheme per witness
+            witness_schemes = match witness_scheme.len() {
+                0 => vec![DEFAULT_WITNESS_SCHEME.to_string(); pub_witness_pem_filenames.len()],
+                1 => vec![witness_scheme[0].clone(); pub_witness_pem_filenames.len()],
+                n if n == pub_witness_pem_filenames.len() => witness_scheme.clone(),
+                n => exit_with(HyperfridgeError::Input(format!(
+                    "{} witness schemes given for {} witness keys",
+                    n,
+                    pub_witness_pem_filenames.len()
+                ))),
+            };
 
-            iban = clientiban.clone();
+            // IBANs are checked here, typos would only show up as missing statements in the guest
+            for account in clientiban {
+                let iban = account.split(':').next().unwrap_or_default();
+                if !is_valid_iban(iban) {
+                    exit_with(HyperfridgeError::Input(format!(
+                        "{} is not a valid IBAN (checksum mod 97)",
+                        iban
+                    )));
+                }
+            }
+            accounts = clientiban
+                .iter()
+                .chain(clientaccount.iter())
+                .cloned()
+                .collect();
+            challenge = verifier_challenge.clone().unwrap_or_default();
+            bind_to = binding.clone().unwrap_or_default();
+            bind_check = *check_binding;
+            include_pending = *pending;
+            tx_codes = tx_code.clone();
+            policy = policy_file
+                .as_ref()
+                .map(|path| {
+                    fs::read_to_string(path).unwrap_or_else(|_| {
+                        exit_with(HyperfridgeError::Io(format!(
+                            "Failed to read policy file {:?}",
+                            path
+                        )))
+                    })
+                })
+                .into_iter()
+                .chain([policy_shortcuts(
+                    *hash_iban,
+                    *fingerprint_keys,
+                    salt.as_deref(),
+                )])
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<String>>()
+                .join("\n");
+            let read = |path: &Option<PathBuf>| {
+                path.as_ref()
+                    .map(|path| {
+                        fs::read_to_string(path).unwrap_or_else(|_| {
+                            exit_with(HyperfridgeError::Io(format!(
+                                "Failed to read file {:?}",
+                                path
+                            )))
+                        })
+                    })
+                    .unwrap_or_default()
+            };
+            fx = FxInput {
+                reporting_ccy: reporting_ccy.clone().unwrap_or_default(),
+                rates: read(fx_rates),
+                signature_hex: read(fx_signature),
+                pub_key_pem: read(fx_key),
+                scheme: fx_scheme.clone(),
+            };
+            let to_string = |path: &PathBuf| path.to_str().unwrap().to_string();
+            reserves = ReservesInput {
+                requests: reserves_request.iter().map(to_string).collect(),
+                bankkeys: reserves_bankkey.iter().map(to_string).collect(),
+                // one client key for all responses or one per response
+                clientkeys: match reserves_clientkey.len() {
+                    0 => vec![client_pem_filename.clone(); reserves_request.len()],
+                    1 => vec![to_string(&reserves_clientkey[0]); reserves_request.len()],
+                    _ => reserves_clientkey.iter().map(to_string).collect(),
+                },
+                date: reserves_date.clone().unwrap_or_default(),
+                liabilities: liability.clone(),
+            };
+            if reserves.bankkeys.len() != reserves.requests.len()
+                || reserves.clientkeys.len() != reserves.requests.len()
+            {
+                exit_with(HyperfridgeError::Input(format!(
+                    "{} further responses need one --reserves-bankkey and --reserves-clientkey each",
+                    reserves.requests.len()
+                )));
+            }
+            if bind_check && bind_to.is_empty() {
+                exit_with(HyperfridgeError::Input(
+                    "--bind-check requires --bind-to".to_string(),
+                ));
+            }
+            if cli.proof == "binding" && bind_to.is_empty() {
+                exit_with(HyperfridgeError::Input(
+                    "--proof binding requires --bind-to".to_string(),
+                ));
+            }
 
             camt53_filename = (*request
                 .as_ref()
@@ -137,7 +441,7 @@ fn main() {
                     &camt53_filename,
                     &pub_bank_pem_filename,
                     &client_pem_filename,
-                    &pub_witness_pem_filename,
+                    &pub_witness_pem_filenames[0],
                 );
                 // call the script with the given parameters
                 let output = Command::new(script_path)
@@ -146,9 +450,14 @@ fn main() {
                     .env("xml_file", &camt53_filename)
                     .env("pub_bank", &pub_bank_pem_filename)
                     .env("client", &client_pem_filename)
-                    .env("pub_witness", &pub_witness_pem_filename)
+                    .env("pub_witness", &pub_witness_pem_filenames[0])
                     .output()
-                    .expect("failed to execute script");
+                    .unwrap_or_else(|e| {
+                        exit_with(HyperfridgeError::Io(format!(
+                            "failed to execute script {}",
+                            e
+                        )))
+                    });
                 // check output of the script
                 if output.status.success() {
                     print_verbose!("Script {:?} executed successfully.", script_path.clone());
@@ -156,11 +465,11 @@ fn main() {
                     eprintln!("Script output:");
                     eprintln!("stdout:\n{}", String::from_utf8_lossy(&output.stdout));
                     eprintln!("stderr:\n{}", String::from_utf8_lossy(&output.stderr));
-                    panic!(
+                    exit_with(HyperfridgeError::Io(format!(
                         "Script {:?} failed with exit code {} - see output above",
                         script_path.clone(),
                         output.status.code().unwrap()
-                    );
+                    )));
                 }
             }
         }
@@ -169,78 +478,122 @@ fn main() {
             print_verbose!("Proofing with test data.");
             pub_bank_pem_filename = TEST_BANKKEY.to_string();
             client_pem_filename = TEST_CLIENTKEY.to_string();
-            pub_witness_pem_filename = TEST_WITNESSKEY.to_string();
-
-            iban = TEST_IBAN.to_string();
+            pub_witness_pem_filenames = vec![TEST_WITNESSKEY.to_string()];
+            witness_schemes = vec![DEFAULT_WITNESS_SCHEME.to_string()];
+            witness_threshold = 1;
+            challenge = String::new();
+            bind_to = String::new();
+            bind_check = false;
+            include_pending = false;
+            tx_codes = Vec::new();
+            policy = String::new();
+            fx = FxInput::default();
+            reserves = ReservesInput::default();
+
+            accounts = vec![TEST_IBAN.to_string()];
             camt53_filename = TEST_EBICS_FILE.to_string();
         }
         // user wants to see the image id
         Some(Commands::ShowImageId) => {
-            println!("{}", get_image_id_hex());
+            println!("{}", get_image_id_hex(&cli.proof));
             std::process::exit(0);
         }
-        None => {
-            panic!(" no command given")
-        }
+        None => exit_with(HyperfridgeError::Input("no command given".to_string())),
     }
 
     // we need to do the proofing now, either with test data or with the given parameters
 
     // prepare the files for the proofing
-    let bank_public_key_x002_pem =
-        fs::read_to_string(&pub_bank_pem_filename).expect("Failed to read bank_public_key file");
-    let user_private_key_e002_pem =
-        fs::read_to_string(&client_pem_filename).expect("Failed to read user_private_key file");
-    let pub_witness_pem = fs::read_to_string(&pub_witness_pem_filename)
-        .expect("Failed to read pub_witness_pem_filename file");
-
-    let iban = iban.clone();
-    let camt53_filename: String = camt53_filename.to_string();
+    let bank_public_key_x002_pem = fs::read_to_string(&pub_bank_pem_filename).unwrap_or_else(|e| {
+        exit_with(HyperfridgeError::Io(format!(
+            "Failed to read bank_public_key file {}",
+            e
+        )))
+    });
+    let user_private_key_e002_pem = fs::read_to_string(&client_pem_filename).unwrap_or_else(|e| {
+        exit_with(HyperfridgeError::Io(format!(
+            "Failed to read user_private_key file {}",
+            e
+        )))
+    });
+    let pub_witness_pems: Vec<String> = pub_witness_pem_filenames
+        .iter()
+        .map(|filename| {
+            fs::read_to_string(filename).unwrap_or_else(|_| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Failed to read pub_witness_pem file {}",
+                    filename
+                )))
+            })
+        })
+        .collect();
+
     //<SignedInfo> <authenticated> <SignatureValue> <OrderData>
     // Load files based on command-line arguments
+    let files = read_response_files(&camt53_filename, pub_witness_pems.len());
 
-    // we decrypting the transaction key add around 75k cycles, but the reverse function
-    // encrypting with privte key is much faster. So we expect the decrypted transaction
-    // key, encrypt it and check if it matches with the encrypted transaction key
-    // in the XML file.
-    let decrypted_tx_key_bin_filename = format!("{}-TransactionKeyDecrypt.bin", camt53_filename);
-    print_verbose!("open {}", &decrypted_tx_key_bin_filename);
-    let decrypted_tx_key_bin: Vec<u8> =
-        fs::read(&decrypted_tx_key_bin_filename).unwrap_or_else(|_| {
-            panic!(
-                "Failed to read decrypted transaction key file  {}",
-                decrypted_tx_key_bin_filename.clone()
+    // further responses for the proof of reserves, pre-processed as the first one
+    let reserves_responses: Vec<ResponseInput> = reserves
+        .requests
+        .iter()
+        .enumerate()
+        .map(|(index, filename)| {
+            let files = read_response_files(filename, pub_witness_pems.len());
+            let bank_pem = fs::read_to_string(&reserves.bankkeys[index]).unwrap_or_else(|e| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Failed to read bank_public_key file of further response {}",
+                    e
+                )))
+            });
+            let client_pem = fs::read_to_string(&reserves.clientkeys[index]).unwrap_or_else(|e| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Failed to read user_private_key file of further response {}",
+                    e
+                )))
+            });
+            let (modulus_str, exponent_str) = bank_key_params(&bank_pem);
+            (
+                files.signed_info_xml_c14n,
+                files.authenticated_xml_c14n,
+                files.signature_value_xml,
+                files.order_data_xml,
+                modulus_str,
+                exponent_str,
+                client_pem,
+                files.decrypted_tx_key_bin,
+                files.witness_signatures_hex,
             )
-        });
+        })
+        .collect();
 
-    // other pre-processed files, mainly to c14n for XML
-    // we expect the files to be present, if not we panic
-    let signed_info_xml_c14n = fs::read_to_string(format!("{}-SignedInfo", camt53_filename))
-        .expect("Failed to read SignedInfo file (ends with -SignedInfo)");
-    let authenticated_xml_c14n = fs::read_to_string(format!("{}-authenticated", camt53_filename))
-        .expect("Failed to read authenticated file (ends with -Tauthenticated)");
-    let signature_value_xml = fs::read_to_string(format!("{}-SignatureValue", camt53_filename))
-        .expect("Failed to read SignatureValue file (ends with -SignatureValue)");
-    let order_data_xml = fs::read_to_string(format!("{}-OrderData", camt53_filename))
-        .expect("Failed to read OrderData file (ends with -OrderData)");
-    let witness_signature_hex = fs::read_to_string(format!("{}-Witness.hex", camt53_filename))
-        .expect("Failed to read Witness.hex signature (ends with -Witness.hex)");
-
-    let image_id_hex = get_image_id_hex();
+    let image_id_hex = get_image_id_hex(&cli.proof);
 
     // do the proofing and get the receipt
     let receipt_result = proove_camt53(
-        &signed_info_xml_c14n,
-        &authenticated_xml_c14n,
-        &signature_value_xml,
-        &order_data_xml,
+        &cli.proof,
+        &files.signed_info_xml_c14n,
+        &files.authenticated_xml_c14n,
+        &files.signature_value_xml,
+        &files.order_data_xml,
         &bank_public_key_x002_pem,
         &user_private_key_e002_pem,
-        &decrypted_tx_key_bin,
-        &iban,
-        &witness_signature_hex,
-        &pub_witness_pem,
+        &files.decrypted_tx_key_bin,
+        &accounts,
+        &files.witness_signatures_hex,
+        &pub_witness_pems,
+        &witness_schemes,
+        witness_threshold,
         "host:main",
+        &challenge,
+        &bind_to,
+        bind_check,
+        include_pending,
+        &tx_codes,
+        &policy,
+        &fx,
+        &reserves_responses,
+        &reserves.date,
+        &reserves.liabilities,
     );
     // process result
     match &receipt_result {
@@ -283,7 +636,9 @@ fn main() {
                         .join("_");
                     receipt_file_id = joined_elctrnc_seq_nb.clone();
 
-                    print!("{:#?}", commitment)
+                    print_verbose!("{} proof with image ID {}", commitment.proof, &image_id_hex);
+                    print!("{:#?}", commitment);
+                    print_balances(&commitment)
                 }
                 Err(e) => {
                     receipt_file_id = "commit_json_error".to_owned();
@@ -300,37 +655,58 @@ fn main() {
                 "{}-Receipt-{}-{}-{}.json",
                 &camt53_filename, &image_id_hex, &receipt_file_id, &timestamp_string
             );
-            let mut file = File::create(&file_name)
-                .unwrap_or_else(|_| panic!("Unable to create file {}", &file_name));
+            let mut file = File::create(&file_name).unwrap_or_else(|_| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Unable to create file {}",
+                    &file_name
+                )))
+            });
 
             file.write_all(receipt_json_string.as_bytes())
-                .unwrap_or_else(|_| panic!("Unable to write data in file {}", &file_name));
+                .unwrap_or_else(|_| {
+                    exit_with(HyperfridgeError::Io(format!(
+                        "Unable to write data in file {}",
+                        &file_name
+                    )))
+                });
 
             print_verbose!(" wrote receipt to {}", &file_name);
 
             // filename with -latest.json
-            let file_name = format!(
-                "{}-Receipt-{}-latest.json",
-                &camt53_filename,
-                get_image_id_hex()
-            );
-            let mut file = File::create(&file_name)
-                .unwrap_or_else(|_| panic!("Unable to create file {}", &file_name));
+            let file_name = format!("{}-Receipt-{}-latest.json", &camt53_filename, &image_id_hex);
+            let mut file = File::create(&file_name).unwrap_or_else(|_| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Unable to create file {}",
+                    &file_name
+                )))
+            });
 
             file.write_all(receipt_json_string.as_bytes())
                 .unwrap_or_else(|_| {
-                    panic!("Unable to write data in latest file {} (main)", &file_name)
+                    exit_with(HyperfridgeError::Io(format!(
+                        "Unable to write data in latest file {} (main)",
+                        &file_name
+                    )))
                 });
 
             print_verbose!(" wrote receipt to {}", &file_name);
         }
         Err(e) => {
             print_verbose!("Receipt error in proove_camt53 {:?}", e);
-            panic!("Creating the proof failed {}", e)
+            // a failure in the guest has the HyperfridgeError in the message of the panic
+            let error = HyperfridgeError::from_guest(&format!("{:#}", e));
+            eprintln!("Creating the proof failed");
+            exit_with(error)
         }
     }
 }
 
+/// Prints the error and exits with its exit code, see `HyperfridgeError::exit_code`
+fn exit_with(error: HyperfridgeError) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(error.exit_code())
+}
+
 fn is_verbose() -> String {
     match std::env::var("FRIDGE_VERBOSE") {
         Ok(value) if value == "1" || value.eq_ignore_ascii_case("true") => "verbose".to_string(),
@@ -344,9 +720,107 @@ fn is_verbose() -> String {
     }
 }
 
+/// one line per balance: account, statement, balance type, date and signed amount
+fn print_balances(commitment: &Commitment) {
+    for stmt in commitment.stmts.iter() {
+        for balance in stmt.balances.iter() {
+            print_verbose!(
+                "{} #{} {} {} {}{} {}",
+                if stmt.iban.is_empty() {
+                    &stmt.othr_id
+                } else {
+                    &stmt.iban
+                },
+                stmt.elctrnc_seq_nb,
+                balance.cd,
+                balance.dt,
+                if balance.cdt_dbt_ind == "DBIT" {
+                    "-"
+                } else {
+                    ""
+                },
+                balance.amt,
+                balance.ccy
+            );
+        }
+    }
+    if let Some(reserves) = &commitment.reserves {
+        for total in reserves.totals.iter() {
+            print_verbose!(
+                "reserves {} of {} accounts {} minor units {}",
+                reserves.date,
+                total.accounts,
+                total.total_minor,
+                total.ccy
+            );
+        }
+        for liability in reserves.liabilities.iter() {
+            print_verbose!(
+                "liability {} minor units {} {}",
+                liability.amt_minor,
+                liability.ccy,
+                if liability.covered {
+                    "covered"
+                } else {
+                    "NOT covered"
+                }
+            );
+        }
+    }
+    if let Some(fx) = &commitment.fx {
+        print_verbose!(
+            "total of {} accounts {} minor units {} (rates of {})",
+            fx.accounts,
+            fx.total_minor,
+            fx.reporting_ccy,
+            fx.rate_date
+        );
+    }
+}
+
+/// disclosure policy for --hash-iban and --fingerprint-keys, appended to the --policy file.
+/// --hash-iban hashes every field carrying the account or linking to its owner, the guest
+/// rejects a policy which hides the IBAN but leaves one of them in cleartext.
+fn policy_shortcuts(hash_iban: bool, fingerprint_keys: bool, salt: Option<&str>) -> String {
+    let mut lines = Vec::new();
+    if let Some(salt) = salt {
+        lines.push(format!("salt={}", salt));
+    }
+    if hash_iban {
+        for field in [
+            "accounts",
+            "stmts.iban",
+            "stmts.othr_id",
+            "bind_to",
+            "payments.msg_id",
+            "payments.orgnl_msg_id",
+            "payments.pmt_inf_id",
+            "reserves.pub_bank_fingerprint",
+        ] {
+            lines.push(format!("{}=hash", field));
+        }
+    }
+    if fingerprint_keys {
+        for field in ["pub_bank_pem", "pub_client_pem", "pub_witness_pems"] {
+            lines.push(format!("{}=fingerprint", field));
+        }
+    }
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// flags for the guest, separated by blank
+fn guest_flags(include_pending: bool) -> String {
+    let mut flags = vec![is_verbose()];
+    if include_pending {
+        flags.push("include_pending".to_string());
+    }
+    flags.join(" ")
+}
+
 /// Generates the proof of computation and returning the receipt as JSON
 #[allow(clippy::too_many_arguments)]
 fn proove_camt53(
+    proof: &str,
     signed_info_xml_c14n: &str,
     authenticated_xml_c14n: &str,
     signature_value_xml: &str,
@@ -354,14 +828,26 @@ fn proove_camt53(
     bank_public_key_x002_pem: &str,
     user_private_key_e002_pem: &str,
     decrypted_tx_key_bin: &Vec<u8>,
-    iban: &str,
-    witness_signature_hex: &str,
-    pub_witness_pem: &str,
+    accounts: &[String],
+    witness_signatures_hex: &[String],
+    pub_witness_pems: &[String],
+    witness_schemes: &[String],
+    witness_threshold: u32,
     host_info: &str,
+    challenge: &str,
+    bind_to: &str,
+    bind_check: bool,
+    include_pending: bool,
+    tx_codes: &[String],
+    policy: &str,
+    fx: &FxInput,
+    reserves_responses: &[ResponseInput],
+    reserves_date: &str,
+    liabilities: &[String],
 ) -> Result<Receipt, anyhow::Error> {
     print_verbose!("start: {}", Local::now().format("%Y-%m-%d %H:%M:%S"));
     // write image ID to filesystem
-    let _ = write_image_id();
+    let _ = write_image_id(proof);
 
     // Using r0 implementation crypto-bigint does not work with RsaPUblicKey?
     // ==> Research shows not - needs reimplementation of RSA modue which might speed things up.
@@ -380,11 +866,7 @@ fn proove_camt53(
     // https://docs.rs/risc0-zkvm/latest/risc0_zkvm/struct.ExecutorEnvBuilder.html
     print_verbose!("Starting guest code, load environment");
     env_logger::init();
-    let pem = parse(bank_public_key_x002_pem).expect("Failed to parse bank public key PEM");
-    let bank_public_key = RsaPublicKey::from_public_key_pem(&pem::encode(&pem))
-        .expect("Failed to create bank public key");
-    let modulus_str = bank_public_key.n().to_str_radix(10);
-    let exponent_str = bank_public_key.e().to_str_radix(10);
+    let (modulus_str, exponent_str) = bank_key_params(bank_public_key_x002_pem);
 
     let env = ExecutorEnv::builder()
         .write(&signed_info_xml_c14n)
@@ -403,29 +885,60 @@ fn proove_camt53(
         .unwrap()
         .write(&decrypted_tx_key_bin)
         .unwrap()
-        .write(&iban)
+        .write(&accounts)
         .unwrap()
         .write(&host_info)
         .unwrap()
-        .write(&witness_signature_hex)
+        .write(&challenge)
+        .unwrap()
+        .write(&bind_to)
+        .unwrap()
+        .write(&bind_check)
+        .unwrap()
+        .write(&witness_signatures_hex)
+        .unwrap()
+        .write(&pub_witness_pems)
         .unwrap()
-        .write(&pub_witness_pem)
+        .write(&witness_schemes)
         .unwrap()
-        .write(&is_verbose())
+        .write(&witness_threshold)
+        .unwrap()
+        .write(&tx_codes)
+        .unwrap()
+        .write(&policy)
+        .unwrap()
+        .write(&fx.reporting_ccy)
+        .unwrap()
+        .write(&fx.rates)
+        .unwrap()
+        .write(&fx.signature_hex)
+        .unwrap()
+        .write(&fx.pub_key_pem)
+        .unwrap()
+        .write(&fx.scheme)
+        .unwrap()
+        .write(&reserves_responses)
+        .unwrap()
+        .write(&reserves_date)
+        .unwrap()
+        .write(&liabilities)
+        .unwrap()
+        .write(&guest_flags(include_pending))
         .unwrap()
         .build()
         .unwrap();
 
     // Obtain the default prover.
     let prover = default_prover();
-    print_verbose!("prove hyperfridge elf ");
+    let (elf, _) = guest_image(proof);
+    print_verbose!("prove hyperfridge elf of {} proof", proof);
     // generate receipt (risc0 1.x returns ProveInfo, extract .receipt)
-    let receipt_result = prover.prove(env, HYPERFRIDGE_ELF).map(|info| info.receipt);
-    let image_id_hex = get_image_id_hex();
+    let receipt_result = prover.prove(env, elf).map(|info| info.receipt);
+    let image_id_hex = get_image_id_hex(proof);
     print_verbose!(
         "got the receipt of the prove , id first 32u {} binary size of ELF binary {}k",
         image_id_hex,
-        HYPERFRIDGE_ELF.len() / 1000
+        elf.len() / 1000
     );
     receipt_result
 }
@@ -433,17 +946,164 @@ fn proove_camt53(
 use std::fs::File;
 use std::io::{Error, Write};
 
-fn write_image_id() -> Result<(), Error> {
+fn write_image_id(proof: &str) -> Result<(), Error> {
     // Write hex string to IMAGE_ID.hex
     let mut hex_file = File::create("./out/IMAGE_ID.hex")?;
-    hex_file.write_all(get_image_id_hex().as_bytes())?;
+    hex_file.write_all(get_image_id_hex(proof).as_bytes())?;
 
     Ok(())
 }
 
-/// get image_id to a hexadecimal string
-fn get_image_id_hex() -> String {
-    HYPERFRIDGE_ID
+/// file name of the signature of the witness with the given index
+fn witness_signature_filename(camt53_filename: &str, index: usize) -> String {
+    if index == 0 {
+        format!("{}-Witness.hex", camt53_filename)
+    } else {
+        format!("{}-Witness-{}.hex", camt53_filename, index)
+    }
+}
+
+/// Pre-processed files of an EBICS response, named after the response file
+struct ResponseFiles {
+    signed_info_xml_c14n: String,
+    authenticated_xml_c14n: String,
+    signature_value_xml: String,
+    order_data_xml: String,
+    decrypted_tx_key_bin: Vec<u8>,
+    witness_signatures_hex: Vec<String>,
+}
+
+fn read_response_files(camt53_filename: &str, witnesses: usize) -> ResponseFiles {
+    // we decrypting the transaction key add around 75k cycles, but the reverse function
+    // encrypting with privte key is much faster. So we expect the decrypted transaction
+    // key, encrypt it and check if it matches with the encrypted transaction key
+    // in the XML file.
+    let decrypted_tx_key_bin_filename = format!("{}-TransactionKeyDecrypt.bin", camt53_filename);
+    print_verbose!("open {}", &decrypted_tx_key_bin_filename);
+    let decrypted_tx_key_bin: Vec<u8> =
+        fs::read(&decrypted_tx_key_bin_filename).unwrap_or_else(|_| {
+            exit_with(HyperfridgeError::Io(format!(
+                "Failed to read decrypted transaction key file  {}",
+                decrypted_tx_key_bin_filename.clone()
+            )))
+        });
+
+    // other pre-processed files, mainly to c14n for XML
+    // we expect the files to be present, if not we exit with an Io error
+    ResponseFiles {
+        signed_info_xml_c14n: fs::read_to_string(format!("{}-SignedInfo", camt53_filename))
+            .unwrap_or_else(|e| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Failed to read SignedInfo file (ends with -SignedInfo) {}",
+                    e
+                )))
+            }),
+        authenticated_xml_c14n: fs::read_to_string(format!("{}-authenticated", camt53_filename))
+            .unwrap_or_else(|e| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Failed to read authenticated file (ends with -Tauthenticated) {}",
+                    e
+                )))
+            }),
+        signature_value_xml: fs::read_to_string(format!("{}-SignatureValue", camt53_filename))
+            .unwrap_or_else(|e| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Failed to read SignatureValue file (ends with -SignatureValue) {}",
+                    e
+                )))
+            }),
+        order_data_xml: fs::read_to_string(format!("{}-OrderData", camt53_filename))
+            .unwrap_or_else(|e| {
+                exit_with(HyperfridgeError::Io(format!(
+                    "Failed to read OrderData file (ends with -OrderData) {}",
+                    e
+                )))
+            }),
+        decrypted_tx_key_bin,
+        // signature of the first witness is in -Witness.hex, others in -Witness-1.hex, -Witness-2.hex..
+        // a missing file means that this witness did not sign, the guest checks the threshold.
+        witness_signatures_hex: (0..witnesses)
+            .map(|index| {
+                let filename = witness_signature_filename(camt53_filename, index);
+                fs::read_to_string(&filename).unwrap_or_else(|_| {
+                    print_verbose!("no witness signature found in {}", &filename);
+                    String::new()
+                })
+            })
+            .collect(),
+    }
+}
+
+/// modulus and exponent of the bank key as decimal numbers, as the guest expects them
+fn bank_key_params(bank_public_key_x002_pem: &str) -> (String, String) {
+    let pem = parse(bank_public_key_x002_pem).unwrap_or_else(|e| {
+        exit_with(HyperfridgeError::Input(format!(
+            "Failed to parse bank public key PEM {}",
+            e
+        )))
+    });
+    let bank_public_key =
+        RsaPublicKey::from_public_key_pem(&pem::encode(&pem)).unwrap_or_else(|e| {
+            exit_with(HyperfridgeError::Input(format!(
+                "Failed to create bank public key {}",
+                e
+            )))
+        });
+    (
+        bank_public_key.n().to_str_radix(10),
+        bank_public_key.e().to_str_radix(10),
+    )
+}
+
+/// IBAN checksum as of ISO 13616: move country code and check digits to the end,
+/// replace letters by 10..35 and the remainder mod 97 must be 1
+fn is_valid_iban(iban: &str) -> bool {
+    let iban: String = iban.chars().filter(|c| !c.is_whitespace()).collect();
+    if iban.len() < 5 || iban.len() > 34 || !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    let (country, rest) = iban.split_at(2);
+    if !country.chars().all(|c| c.is_ascii_alphabetic())
+        || !rest[..2].chars().all(|c| c.is_ascii_digit())
+    {
+        return false;
+    }
+    let remainder = rest[2..]
+        .chars()
+        .chain(iban[..4].chars())
+        .fold(0u32, |remainder, c| {
+            let value = c.to_digit(36).expect("alphanumeric");
+            if value < 10 {
+                (remainder * 10 + value) % 97
+            } else {
+                (remainder * 100 + value) % 97
+            }
+        });
+    remainder == 1
+}
+
+/// Guest images by proof type: name, ELF binary and image ID. The full proof has all
+/// predicates, the others commit less and need fewer cycles.
+const GUEST_IMAGES: &[(&str, &[u8], [u32; 8])] = &[
+    ("full", HYPERFRIDGE_ELF, HYPERFRIDGE_ID),
+    ("balance", BALANCE_ELF, BALANCE_ID),
+    ("binding", BINDING_ELF, BINDING_ID),
+    ("aggregates", AGGREGATES_ELF, AGGREGATES_ID),
+];
+
+/// ELF binary and image ID of the guest for a proof type
+fn guest_image(proof: &str) -> (&'static [u8], [u32; 8]) {
+    GUEST_IMAGES
+        .iter()
+        .find(|(name, _, _)| *name == proof)
+        .map(|(_, elf, image_id)| (*elf, *image_id))
+        .unwrap_or_else(|| panic!("unknown proof type {}", proof))
+}
+
+/// get image_id of the guest for a proof type to a hexadecimal string
+fn get_image_id_hex(proof: &str) -> String {
+    guest_image(proof)
+        .1
         .iter()
         .fold(String::new(), |acc, &num| acc + &format!("{:08x}", num))
 }
@@ -453,6 +1113,8 @@ const TEST_IBAN: &str = "CH4308307000289537312";
 const TEST_BANKKEY: &str = "../data/pub_bank.pem";
 const TEST_CLIENTKEY: &str = "../data/client.pem";
 const TEST_WITNESSKEY: &str = "../data/pub_witness.pem";
+// EBICS A005 - RSA with PKCS#1 v1.5 padding, as created by checkResponse.sh
+const DEFAULT_WITNESS_SCHEME: &str = "rsa-pkcs1";
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -477,6 +1139,15 @@ struct Cli {
     #[arg(long, hide = true)]
     markdown_help: bool,
 
+    #[arg(
+        long,
+        global = true,
+        default_value = "full",
+        value_parser = ["full", "balance", "binding", "aggregates"],
+        help = "Proof type, each has its own guest image and image ID: full, balance (balances only), binding (a booked outgoing transfer carries --bind-to, proving control of the account, no amounts) or aggregates (balances, entry totals, reserves and FX total)."
+    )]
+    proof: String,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -523,19 +1194,42 @@ enum Commands {
         #[arg(
             short,
             long,
-            help = "PEM for the public key of the witness.",
+            help = "PEM for the public key of the witness. Repeat for several witnesses; the signature of the first witness is read from <request>-Witness.hex, the others from <request>-Witness-1.hex, <request>-Witness-2.hex...",
             value_name = "FILE",
             required = true
         )]
-        witnesskey: Option<PathBuf>,
+        witnesskey: Vec<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "1",
+            help = "Number of distinct witnesses which need to sign the order data."
+        )]
+        witness_threshold: u32,
+
+        #[arg(
+            long,
+            value_parser = clap::builder::PossibleValuesParser::new(["rsa-pkcs1", "rsa-pss", "ecdsa-p256", "ed25519"]),
+            help = "Signature scheme of the witness: rsa-pkcs1 (A005, default), rsa-pss (A006), ecdsa-p256 or ed25519. Give once for all witnesses or once per witness key."
+        )]
+        witness_scheme: Vec<String>,
 
         #[arg(
             short = 'i',
             long,
-            help = "IBAN of the account as used in camt53 files. Account statements not referring to this IBAN will be ignored when generating the proof.",
-            required = true
+            value_delimiter = ',',
+            help = "IBAN of the account as used in camt53 files, or IBAN:CCY for a currency sub-account. Repeat (or separate by comma) to prove several accounts; account statements not referring to these accounts will be ignored when generating the proof.",
+            required_unless_present = "clientaccount"
         )]
-        clientiban: String,
+        clientiban: Vec<String>,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Account without IBAN, identified by <Othr><Id> in camt53 files: ID or SCHEME/ID (scheme name code or proprietary, e.g. BBAN/123456789), optionally followed by :CCY. Repeat (or separate by comma) for several accounts.",
+            required = false
+        )]
+        clientaccount: Vec<String>,
 
         #[arg(
             short,
@@ -544,6 +1238,157 @@ enum Commands {
             required = false
         )]
         script: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Challenge (nonce) of the verifier which is committed in the receipt, so the receipt can only be used for this verification request.",
+            required = false
+        )]
+        challenge: Option<String>,
+
+        #[arg(
+            long,
+            help = "On-chain identity (e.g. SS58 or EVM address, DID) which is committed in the receipt as the party allowed to use the proof.",
+            required = false
+        )]
+        bind_to: Option<String>,
+
+        #[arg(
+            long,
+            default_value = "false",
+            help = "Require an outgoing transfer from the account with the --bind-to value in AddtlTxInf - proves that the account holder controls the IBAN.",
+            required = false
+        )]
+        bind_check: bool,
+
+        #[arg(
+            long,
+            default_value = "false",
+            help = "Count pending entries (PDNG) towards the committed in- and outflows, by default only booked entries count.",
+            required = false
+        )]
+        include_pending: bool,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Bank transaction code selecting the entries of the committed in- and outflows: DOMN/FMLY/SUBFMLY or a prefix (e.g. PMNT/RCDT for received transfers), PRTRY:CODE for proprietary codes. Repeat (or separate by comma) for several codes, all entries if omitted.",
+            required = false
+        )]
+        tx_code: Vec<String>,
+
+        #[arg(
+            long,
+            help = "Disclosure policy file deciding per committed field: clear, hash (salted) or omit. Only the hash of the policy is committed, all fields are in cleartext if omitted.",
+            value_name = "FILE",
+            required = false
+        )]
+        policy: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = "false",
+            requires = "salt",
+            help = "Commit the accounts, the IBAN (or other account id) of the statements, bind_to, the payment message ids and the bank fingerprints of a proof of reserves as salted hash sha256(salt || value) instead of cleartext.",
+            required = false
+        )]
+        hash_iban: bool,
+
+        #[arg(
+            long,
+            default_value = "false",
+            help = "Commit the public keys of bank, client and witnesses as fingerprint sha256(DER) instead of PEM.",
+            required = false
+        )]
+        fingerprint_keys: bool,
+
+        #[arg(
+            long,
+            help = "Salt for hashed fields, at least 16 characters - share it off-chain with the verifier only.",
+            required = false
+        )]
+        salt: Option<String>,
+
+        #[arg(
+            long,
+            help = "Reporting currency (ISO 4217) - commits the total of the closing balances of all accounts converted with --fx-rates.",
+            required = false
+        )]
+        reporting_ccy: Option<String>,
+
+        #[arg(
+            long,
+            requires_all = ["reporting_ccy", "fx_signature", "fx_key"],
+            help = "FX rates of the rate provider, one rate per line as CCY/REPORTING_CCY=RATE, e.g. EUR/CHF=0.9512, and date=YYYY-MM-DD.",
+            value_name = "FILE",
+            required = false
+        )]
+        fx_rates: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Signature of the rate provider over the SHA-256 hash of the --fx-rates file, hex encoded.",
+            value_name = "FILE",
+            required = false
+        )]
+        fx_signature: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Public key of the rate provider as PEM.",
+            value_name = "FILE",
+            required = false
+        )]
+        fx_key: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value = DEFAULT_WITNESS_SCHEME,
+            value_parser = clap::builder::PossibleValuesParser::new(["rsa-pkcs1", "rsa-pss", "ecdsa-p256", "ed25519"]),
+            help = "Signature scheme of the rate provider, as for --witness-scheme."
+        )]
+        fx_scheme: String,
+
+        #[arg(
+            long,
+            help = "Date (YYYY-MM-DD) of the proof of reserves - commits the total of the latest closing balances up to this date per currency.",
+            required = false
+        )]
+        reserves_date: Option<String>,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            requires = "reserves_date",
+            help = "Public liabilities as CCY=AMOUNT, e.g. CHF=1500000.00, the receipt commits whether the reserves cover them. Repeat (or separate by comma) for several currencies.",
+            required = false
+        )]
+        liability: Vec<String>,
+
+        #[arg(
+            long,
+            requires = "reserves_date",
+            help = "Further pre-processed EBICS response for the proof of reserves, e.g. of another bank. Repeat for several responses.",
+            value_name = "FILE",
+            required = false
+        )]
+        reserves_request: Vec<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Public key of the bank of each --reserves-request, in the same order.",
+            value_name = "FILE",
+            required = false
+        )]
+        reserves_bankkey: Vec<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Private client key for the --reserves-request responses: once for all or once per response, default is --clientkey.",
+            value_name = "FILE",
+            required = false
+        )]
+        reserves_clientkey: Vec<PathBuf>,
     },
     /// Uses test data - sample call is:
     /// RUST_BACKTRACE=1 RISC0_DEV_MODE=true cargo run  -- --verbose test
@@ -567,7 +1412,8 @@ fn parse_cli() -> Cli {
 mod tests {
     use crate::fs;
     use crate::{
-        get_image_id_hex, proove_camt53, TEST_BANKKEY, TEST_CLIENTKEY, TEST_EBICS_FILE, TEST_IBAN,
+        get_image_id_hex, guest_image, is_valid_iban, policy_shortcuts, proove_camt53, FxInput,
+        DEFAULT_WITNESS_SCHEME, TEST_BANKKEY, TEST_CLIENTKEY, TEST_EBICS_FILE, TEST_IBAN,
         TEST_WITNESSKEY,
     };
 
@@ -592,6 +1438,7 @@ mod tests {
                 .expect("Failed to read transaction key file");
         // run the proofing with static test data
         let receipt_result = proove_camt53(
+            "full",
             fs::read_to_string(TEST_EBICS_FILE.to_string() + "-SignedInfo")
                 .unwrap()
                 .as_str(),
@@ -607,12 +1454,22 @@ mod tests {
             fs::read_to_string(TEST_BANKKEY).unwrap().as_str(),
             fs::read_to_string(TEST_CLIENTKEY).unwrap().as_str(),
             decrypted_tx_key_bin,
-            TEST_IBAN,
-            fs::read_to_string(TEST_EBICS_FILE.to_string() + "-Witness.hex")
-                .unwrap()
-                .as_str(),
-            fs::read_to_string(TEST_WITNESSKEY).unwrap().as_str(),
+            &[TEST_IBAN.to_string()],
+            &[fs::read_to_string(TEST_EBICS_FILE.to_string() + "-Witness.hex").unwrap()],
+            &[fs::read_to_string(TEST_WITNESSKEY).unwrap()],
+            &[DEFAULT_WITNESS_SCHEME.to_string()],
+            1,
             &host_info,
+            "",
+            "",
+            false,
+            false,
+            &[],
+            "",
+            &FxInput::default(),
+            &[],
+            "",
+            &[],
         );
         // lets see if the receipt is there
         match &receipt_result {
@@ -635,7 +1492,7 @@ mod tests {
                 let filename = format!(
                     "{}-Receipt-{}-latest.json",
                     TEST_EBICS_FILE,
-                    get_image_id_hex()
+                    get_image_id_hex("full")
                 );
                 let mut file =
                     File::create(filename).expect("Unable to create file with timestamp");
@@ -647,4 +1504,44 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn iban_checksum() {
+        assert!(is_valid_iban(TEST_IBAN));
+        assert!(is_valid_iban("CH2108307000289537320"));
+        assert!(is_valid_iban("GB82 WEST 1234 5698 7654 32"));
+        assert!(!is_valid_iban("CH4308307000289537313"));
+        assert!(!is_valid_iban("123456789"));
+        assert!(!is_valid_iban(""));
+    }
+
+    #[test]
+    fn policy_for_hashed_iban() {
+        assert_eq!(policy_shortcuts(false, false, None), "");
+        assert_eq!(
+            policy_shortcuts(true, false, Some("4f1c0e6b2a93d8e5")),
+            concat!(
+                "salt=4f1c0e6b2a93d8e5\naccounts=hash\nstmts.iban=hash\nstmts.othr_id=hash\n",
+                "bind_to=hash\npayments.msg_id=hash\npayments.orgnl_msg_id=hash\n",
+                "payments.pmt_inf_id=hash\nreserves.pub_bank_fingerprint=hash\n"
+            )
+        );
+        assert_eq!(
+            policy_shortcuts(false, true, None),
+            "pub_bank_pem=fingerprint\npub_client_pem=fingerprint\npub_witness_pems=fingerprint\n"
+        );
+    }
+
+    #[test]
+    fn guest_image_per_proof() {
+        assert_eq!(guest_image("full").1, HYPERFRIDGE_ID);
+        assert_ne!(get_image_id_hex("balance"), get_image_id_hex("full"));
+        assert_ne!(get_image_id_hex("binding"), get_image_id_hex("aggregates"));
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown proof type")]
+    fn guest_image_unknown() {
+        guest_image("solvency");
+    }
 }
@@ -0,0 +1,10 @@
+#![cfg_attr(not(feature = "debug_mode"), no_main)]
+
+// Aggregates proof, the image of AGGREGATES_ELF and AGGREGATES_ID
+
+#[cfg(not(feature = "debug_mode"))]
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    hyperfridge::prove_aggregates();
+}
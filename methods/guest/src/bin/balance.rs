@@ -0,0 +1,10 @@
+#![cfg_attr(not(feature = "debug_mode"), no_main)]
+
+// Balance proof, the image of BALANCE_ELF and BALANCE_ID
+
+#[cfg(not(feature = "debug_mode"))]
+risc0_zkvm::guest::entry!(main);
+
+pub fn main() {
+    hyperfridge::prove_balance();
+}
@@ -0,0 +1,201 @@
+//! Conversion of the closing balances of all accounts into a reporting currency with FX rates
+//! signed by a rate provider. The rate set is a text document with the date of the rates and
+//! one rate per line, quoted as units of the reporting currency for one unit of the currency:
+//!
+//! ```text
+//! date=2023-11-30
+//! EUR/CHF=0.9512
+//! USD/CHF=0.8841
+//! ```
+//!
+//! The rate provider signs the SHA-256 hash of the rate set with one of the witness schemes,
+//! the signature is verified in the guest before any rate is used.
+
+use crate::{closing_balances, currency_exponent, escape_json, policy};
+use crate::{fail, Document, HyperfridgeError, WitnessKey, WitnessScheme};
+use risc0_zkvm::sha::{Impl, Sha256};
+
+/// A rate as fixed point number: `units / 10^scale`
+#[derive(Debug, Clone, PartialEq)]
+struct Rate {
+    from: String,
+    to: String,
+    units: i128,
+    scale: u32,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RateSet {
+    date: String,
+    rates: Vec<Rate>,
+    hash: Vec<u8>, // sha256 of the rate set as signed, empty without rates
+}
+
+impl RateSet {
+    /// parses the rate set, fails on malformed lines
+    pub(crate) fn parse(rates: &str) -> RateSet {
+        let mut parsed = RateSet::default();
+        if rates.trim().is_empty() {
+            return parsed;
+        }
+        parsed.hash = Impl::hash_bytes(rates.as_bytes()).as_bytes().to_vec();
+        for line in rates.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line.split_once('=').unwrap_or_else(|| {
+                fail(HyperfridgeError::Parse(format!(
+                    "invalid FX rate line '{}'",
+                    line
+                )))
+            });
+            let (name, value) = (name.trim(), value.trim());
+            if name == "date" {
+                parsed.date = value.to_string();
+                continue;
+            }
+            let (from, to) = name.split_once('/').unwrap_or_else(|| {
+                fail(HyperfridgeError::Parse(format!(
+                    "invalid FX currency pair '{}'",
+                    name
+                )))
+            });
+            let (units, scale) = parse_rate(value);
+            parsed.rates.push(Rate {
+                from: from.to_string(),
+                to: to.to_string(),
+                units,
+                scale,
+            });
+        }
+        parsed
+    }
+
+    /// verifies the signature of the rate provider, fails with `WitnessInvalid` if it is invalid
+    pub(crate) fn verify(&self, key: &WitnessKey, scheme: WitnessScheme, signature: &[u8]) {
+        ensure!(!self.hash.is_empty(), Input, "no FX rates to verify");
+        key.verify(scheme, &self.hash, signature)
+            .unwrap_or_else(|e| {
+                fail(HyperfridgeError::WitnessInvalid(format!(
+                    "FX rate set signature invalid {}",
+                    e
+                )))
+            });
+    }
+
+    /// converts an amount in minor units of `from` to minor units of `to`,
+    /// rounded half away from zero
+    fn convert(&self, amount_minor: i64, from: &str, to: &str) -> i64 {
+        if from == to {
+            return amount_minor;
+        }
+        let rate = self
+            .rates
+            .iter()
+            .find(|rate| rate.from == from && rate.to == to)
+            .unwrap_or_else(|| {
+                fail(HyperfridgeError::Input(format!(
+                    "no FX rate for {}/{}",
+                    from, to
+                )))
+            });
+        let numerator = amount_minor as i128 * rate.units * 10i128.pow(currency_exponent(to));
+        let denominator = 10i128.pow(rate.scale + currency_exponent(from));
+        let rounded = (numerator.abs() + denominator / 2) / denominator;
+        i64::try_from(numerator.signum() * rounded).unwrap_or_else(|_| {
+            fail(HyperfridgeError::Input(
+                "converted amount out of range".to_string(),
+            ))
+        })
+    }
+}
+
+/// Sum of the closing balances of all accounts in the reporting currency
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct FxTotal {
+    pub(crate) reporting_ccy: String,
+    pub(crate) total_minor: i64,
+    pub(crate) accounts: usize, // number of converted closing balances
+    pub(crate) rate_date: String,
+    pub(crate) rates_hash: String,
+}
+
+impl FxTotal {
+    /// converts the closing booked balance (CLBD) of the last statement of each account, for a
+    /// proof of reserves the latest one up to `reserves_date`. The rates must be of the date of
+    /// these balances, otherwise the proof fails with `Verification`.
+    pub(crate) fn of(
+        documents: &[Document],
+        rates: &RateSet,
+        reporting_ccy: &str,
+        reserves_date: &str,
+    ) -> FxTotal {
+        let until = Some(reserves_date).filter(|date| !date.is_empty());
+        let closing = closing_balances(documents, until);
+        if !rates.hash.is_empty() {
+            let balance_date = match until {
+                Some(date) => date.to_string(),
+                None => closing
+                    .iter()
+                    .map(|balance| balance.dt.get(..10).unwrap_or(&balance.dt).to_string())
+                    .max()
+                    .unwrap_or_default(),
+            };
+            ensure!(
+                rates.date == balance_date,
+                Verification,
+                "FX rates of '{}' are not of the closing balances of {}",
+                rates.date,
+                balance_date
+            );
+        }
+        FxTotal {
+            reporting_ccy: reporting_ccy.to_string(),
+            total_minor: closing
+                .iter()
+                .map(|balance| rates.convert(balance.amt_minor, &balance.ccy, reporting_ccy))
+                .sum(),
+            accounts: closing.len(),
+            rate_date: rates.date.clone(),
+            rates_hash: hex::encode(&rates.hash),
+        }
+    }
+
+    /// the key of the rate provider is only committed if it verified a rate set
+    pub(crate) fn commitment(&self, pub_fx_pem: &str) -> String {
+        format!(
+            "{{\"reporting_ccy\":\"{}\",\"ccy_exp\":{},\"total_minor\":{},\"accounts\":{},\"rate_date\":\"{}\",\"rates_hash\":\"{}\",\"fx_key_fingerprint\":\"{}\"}}",
+            escape_json(&self.reporting_ccy),
+            currency_exponent(&self.reporting_ccy),
+            self.total_minor,
+            self.accounts,
+            escape_json(&self.rate_date),
+            self.rates_hash,
+            if pub_fx_pem.is_empty() || self.rates_hash.is_empty() {
+                String::new()
+            } else {
+                policy::fingerprint(pub_fx_pem)
+            }
+        )
+    }
+}
+
+/// "0.9512" to (9512, 4)
+fn parse_rate(rate: &str) -> (i128, u32) {
+    let (integer, fraction) = rate.split_once('.').unwrap_or((rate, ""));
+    ensure!(
+        !integer.is_empty()
+            && integer.chars().all(|c| c.is_ascii_digit())
+            && fraction.chars().all(|c| c.is_ascii_digit())
+            && integer.len() <= 6
+            && fraction.len() <= 10,
+        Parse,
+        "invalid FX rate {}",
+        rate
+    );
+    let units: i128 = format!("{}{}", integer, fraction)
+        .parse()
+        .unwrap_or_else(|_| fail(HyperfridgeError::Parse(format!("invalid FX rate {}", rate))));
+    ensure!(units > 0, Parse, "FX rate {} must be positive", rate);
+    (units, fraction.len() as u32)
+}
@@ -0,0 +1,1717 @@
+//! Verification of EBICS responses and the commitments of the hyperfridge guests. Each proof
+//! type is built as its own guest image in `src/bin`, so a new predicate does not change the
+//! image ID of the other proof types; `src/main.rs` is the full proof (`HYPERFRIDGE_ELF`).
+
+// If you want to try std support, also update the guest Cargo.toml file
+// #![no_std]  // std support is experimental
+
+use risc0_zkvm::{
+    guest::env,
+    sha::{Impl, Sha256},
+};
+use rsa::BigUint;
+use rsa::{
+    pkcs8::DecodePrivateKey, pkcs8::DecodePublicKey, pkcs8::EncodePublicKey, pkcs8::LineEnding,
+    traits::PublicKeyParts,
+};
+use rsa::{Pkcs1v15Sign, Pss, RsaPrivateKey, RsaPublicKey};
+
+use sha2::Sha256 as RsaSha256;
+
+use hex::FromHex;
+
+use ed25519_dalek::VerifyingKey as Ed25519VerifyingKey;
+use p256::ecdsa::{signature::hazmat::PrehashVerifier, VerifyingKey as P256VerifyingKey};
+
+// verification of the EBICS response and camt53 parsing, shared with native backends
+use hyperfridge_ebics::{
+    decrypt_order_data, decrypt_transaction_key, order_data_hash, parse_camt53,
+    parse_ebics_response, path_ends_with, verify_bank_signature, Balance, BkTxCd, Document,
+    HyperfridgeError, Ntry, Request, Stmt, TxDtls, TxsSummry,
+};
+
+/// `assert!` which fails the guest with a `HyperfridgeError` of the given kind
+macro_rules! ensure {
+    ($cond:expr, $kind:ident, $($arg:tt)+) => {
+        if !$cond {
+            $crate::fail($crate::HyperfridgeError::$kind(format!($($arg)+)));
+        }
+    };
+}
+
+mod fx;
+mod mt940;
+mod pain002;
+mod policy;
+mod reserves;
+#[cfg(test)]
+mod test_xmlparse;
+
+use policy::{json_object, Policy};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+// pending entries count towards in- and outflows
+static INCLUDE_PENDING: AtomicBool = AtomicBool::new(false);
+
+macro_rules! print_verbose {
+    ($($arg:tt)*) => {
+        if VERBOSE.load(Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Ends the guest with the error as panic message, the host recovers the error from the
+/// message of the failed prove and exits with its exit code.
+fn fail(error: HyperfridgeError) -> ! {
+    panic!("{}", error)
+}
+
+/// Totals of all entries of a statement, recomputed from the parsed entries
+#[derive(Debug, Default, PartialEq)]
+struct EntryTotals {
+    nb_of_ntries: usize,
+    sum_minor: i64, // sum of the absolute amounts
+    cdt_nb_of_ntries: usize,
+    cdt_sum_minor: i64,
+    dbt_nb_of_ntries: usize,
+    dbt_sum_minor: i64,
+}
+
+impl EntryTotals {
+    fn of(stmt: &Stmt) -> EntryTotals {
+        let mut totals = EntryTotals::default();
+        for ntry in stmt.ntries.iter() {
+            let amount = to_minor_units(&ntry.amt, &ntry.ccy, "CRDT");
+            totals.nb_of_ntries += 1;
+            totals.sum_minor = add_minor(totals.sum_minor, amount);
+            match ntry.cdtDbtInd.as_str() {
+                "CRDT" => {
+                    totals.cdt_nb_of_ntries += 1;
+                    totals.cdt_sum_minor = add_minor(totals.cdt_sum_minor, amount);
+                }
+                "DBIT" => {
+                    totals.dbt_nb_of_ntries += 1;
+                    totals.dbt_sum_minor = add_minor(totals.dbt_sum_minor, amount);
+                }
+                other => fail(HyperfridgeError::Parse(format!(
+                    "invalid credit debit indicator '{}' for amount {}",
+                    other, ntry.amt
+                ))),
+            }
+        }
+        totals
+    }
+
+    /// compares with <TxsSummry> of the bank, fails with `Inconsistent` on mismatch
+    fn check(&self, summary: &TxsSummry, ccy: &str, stmt: &str) {
+        let checks = [
+            (
+                "TtlNtries/NbOfNtries",
+                &summary.nb_of_ntries,
+                self.nb_of_ntries as i64,
+                false,
+            ),
+            ("TtlNtries/Sum", &summary.sum, self.sum_minor, true),
+            (
+                "TtlCdtNtries/NbOfNtries",
+                &summary.cdt_nb_of_ntries,
+                self.cdt_nb_of_ntries as i64,
+                false,
+            ),
+            (
+                "TtlCdtNtries/Sum",
+                &summary.cdt_sum,
+                self.cdt_sum_minor,
+                true,
+            ),
+            (
+                "TtlDbtNtries/NbOfNtries",
+                &summary.dbt_nb_of_ntries,
+                self.dbt_nb_of_ntries as i64,
+                false,
+            ),
+            (
+                "TtlDbtNtries/Sum",
+                &summary.dbt_sum,
+                self.dbt_sum_minor,
+                true,
+            ),
+        ];
+        for (name, given, computed, is_sum) in checks {
+            if given.is_empty() {
+                continue;
+            }
+            let given_value = if is_sum {
+                to_minor_units(given, ccy, "CRDT")
+            } else {
+                given.parse::<i64>().unwrap_or_else(|_| {
+                    fail(HyperfridgeError::Parse(format!(
+                        "invalid {} '{}'",
+                        name, given
+                    )))
+                })
+            };
+            ensure!(
+                given_value == computed,
+                Inconsistent,
+                "TxsSummry {} of statement {} is {} but entries give {}",
+                name,
+                stmt,
+                given,
+                computed
+            );
+        }
+    }
+
+    fn commitment(&self, checked: bool) -> String {
+        format!(
+            "{{\"nb_of_ntries\":{},\"sum_minor\":{},\"cdt_nb_of_ntries\":{},\"cdt_sum_minor\":{},\"dbt_nb_of_ntries\":{},\"dbt_sum_minor\":{},\"checked\":{}}}",
+            self.nb_of_ntries,
+            self.sum_minor,
+            self.cdt_nb_of_ntries,
+            self.cdt_sum_minor,
+            self.dbt_nb_of_ntries,
+            self.dbt_sum_minor,
+            checked
+        )
+    }
+}
+
+/// currency for the sums of a statement: of the account, else of the entries or balances
+fn stmt_ccy(stmt: &Stmt) -> String {
+    if !stmt.ccy.is_empty() {
+        return stmt.ccy.clone();
+    }
+    stmt.ntries
+        .first()
+        .map(|ntry| ntry.ccy.clone())
+        .or_else(|| stmt.balances.first().map(|balance| balance.ccy.clone()))
+        .unwrap_or_default()
+}
+
+/// recomputes the totals of the entries and checks them against <TxsSummry>
+fn check_txs_summry(stmt: &Stmt) -> EntryTotals {
+    let totals = EntryTotals::of(stmt);
+    if let Some(summary) = &stmt.txs_summry {
+        totals.check(summary, &stmt_ccy(stmt), &stmt.elctrnc_seq_nb);
+    }
+    totals
+}
+/// Bank transaction codes selecting the entries of in- and outflows, given as public input.
+/// `PMNT/RCDT` selects all codes of that family, `PMNT/RCDT/ESCT` a single sub family,
+/// `PRTRY:NTRF` a proprietary code. No codes select all entries.
+#[derive(Debug, Default)]
+struct TxCodeFilter {
+    codes: Vec<String>,
+}
+
+impl TxCodeFilter {
+    fn parse(codes: &[String]) -> TxCodeFilter {
+        let codes: Vec<String> = codes
+            .iter()
+            .map(|code| code.trim().to_string())
+            .filter(|code| !code.is_empty())
+            .collect();
+        for code in codes.iter() {
+            if code.strip_prefix("PRTRY:").is_none() {
+                let parts: Vec<&str> = code.split('/').collect();
+                ensure!(
+                    parts.len() <= 3 && parts.iter().all(|part| !part.is_empty()),
+                    Input,
+                    "invalid bank transaction code {}",
+                    code
+                );
+            }
+        }
+        TxCodeFilter { codes }
+    }
+
+    fn matches(&self, bk_tx_cd: &BkTxCd) -> bool {
+        if self.codes.is_empty() {
+            return true;
+        }
+        let code = bk_tx_cd.code();
+        self.codes
+            .iter()
+            .any(|filter| match filter.strip_prefix("PRTRY:") {
+                Some(prtry) => prtry == bk_tx_cd.prtry,
+                None => code == *filter || code.starts_with(&format!("{}/", filter)),
+            })
+    }
+}
+
+/// Batch bookings (salary runs, collective payments) have one entry with many transaction
+/// details - their number must match <Btch><NbOfTxs> and their sum the amount of the entry.
+/// Every detail of a batch needs an amount in the currency of the entry, the single detail
+/// of another entry is checked only if it has one.
+fn check_batch(ntry: &Ntry) {
+    if let Some(nb_of_txs) = ntry.btchNbOfTxs {
+        ensure!(
+            ntry.txDtls.is_empty() || ntry.txDtls.len() == nb_of_txs,
+            Inconsistent,
+            "batch entry of {} {} has {} transaction details, expected {}",
+            ntry.amt,
+            ntry.ccy,
+            ntry.txDtls.len(),
+            nb_of_txs
+        );
+    }
+    // amounts in other currencies (FX) can not be added up
+    if ntry
+        .txDtls
+        .iter()
+        .any(|tx_dtls| tx_dtls.AmtValue.is_empty() || tx_dtls.AmtCcy != ntry.ccy)
+    {
+        ensure!(
+            ntry.btchNbOfTxs.is_none() && ntry.txDtls.len() == 1,
+            Parse,
+            "batch entry of {} {} has transaction details without an amount in {}",
+            ntry.amt,
+            ntry.ccy,
+            ntry.ccy
+        );
+        return;
+    }
+    if ntry.txDtls.is_empty() {
+        return;
+    }
+    let sum: i64 = ntry
+        .txDtls
+        .iter()
+        .map(|tx_dtls| to_minor_units(&tx_dtls.AmtValue, &tx_dtls.AmtCcy, &tx_dtls.CdtDbtInd))
+        .sum();
+    ensure!(
+        sum == to_minor_units(&ntry.amt, &ntry.ccy, &ntry.cdtDbtInd),
+        Inconsistent,
+        "sum of transaction details does not match batch entry of {} {}",
+        ntry.amt,
+        ntry.ccy
+    );
+}
+
+/// Category of an entry by status and reversal indicator, only booked entries count
+/// towards the proven in- and outflows unless pending entries are included by flag
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EntryCategory {
+    Booked,
+    Pending,
+    Info,
+    Reversed,
+}
+
+impl EntryCategory {
+    fn of(ntry: &Ntry) -> EntryCategory {
+        if ntry.rvslInd {
+            return EntryCategory::Reversed;
+        }
+        match ntry.sts.as_str() {
+            "BOOK" => EntryCategory::Booked,
+            "PDNG" | "FUTR" => EntryCategory::Pending,
+            "INFO" => EntryCategory::Info,
+            _ => fail(HyperfridgeError::Parse(format!(
+                "unknown status '{}' of entry",
+                ntry.sts
+            ))),
+        }
+    }
+
+    /// entry is part of in- and outflows
+    fn counts(&self) -> bool {
+        match self {
+            EntryCategory::Booked => true,
+            EntryCategory::Pending => INCLUDE_PENDING.load(Ordering::Relaxed),
+            EntryCategory::Info | EntryCategory::Reversed => false,
+        }
+    }
+}
+
+/// Entries of a statement per category and the sum of counted entries in minor units,
+/// only entries selected by the bank transaction codes are summarized
+#[derive(Debug, Default, PartialEq)]
+struct EntrySummary {
+    booked: usize,
+    pending: usize,
+    info: usize,
+    reversed: usize,
+    txs: usize, // individual transactions of the counted entries, batches expanded
+    ccy: String,
+    inflow_minor: i64,
+    outflow_minor: i64,
+}
+
+impl EntrySummary {
+    fn of(stmt: &Stmt, tx_codes: &TxCodeFilter) -> EntrySummary {
+        let mut summary = EntrySummary::default();
+        for ntry in stmt
+            .ntries
+            .iter()
+            .filter(|ntry| tx_codes.matches(&ntry.bkTxCd))
+        {
+            let category = EntryCategory::of(ntry);
+            match category {
+                EntryCategory::Booked => summary.booked += 1,
+                EntryCategory::Pending => summary.pending += 1,
+                EntryCategory::Info => summary.info += 1,
+                EntryCategory::Reversed => summary.reversed += 1,
+            }
+            if !category.counts() {
+                continue;
+            }
+            summary.txs += ntry.transactions().len();
+            if summary.ccy.is_empty() {
+                summary.ccy = ntry.ccy.clone();
+            }
+            ensure!(
+                summary.ccy == ntry.ccy,
+                Inconsistent,
+                "entries of statement {} in several currencies",
+                stmt.elctrnc_seq_nb
+            );
+            let amount = to_minor_units(&ntry.amt, &ntry.ccy, &ntry.cdtDbtInd);
+            if amount >= 0 {
+                summary.inflow_minor += amount;
+            } else {
+                summary.outflow_minor -= amount;
+            }
+        }
+        summary
+    }
+
+    fn commitment(&self) -> String {
+        format!(
+            "{{\"booked\":{},\"pending\":{},\"info\":{},\"reversed\":{},\"txs\":{},\"ccy\":\"{}\",\"inflow_minor\":{},\"outflow_minor\":{}}}",
+            self.booked,
+            self.pending,
+            self.info,
+            self.reversed,
+            self.txs,
+            escape_json(&self.ccy),
+            self.inflow_minor,
+            self.outflow_minor
+        )
+    }
+}
+
+/// Account to be proven, given by the host as `ID` or `SCHEME/ID` (for `<Othr>` identifiers
+/// with scheme name), followed by `:CCY` for currency sub-accounts. `ID` is an IBAN or the
+/// `<Othr><Id>` of the account. MT940 accounts in the form `BLZ/account number` are matched
+/// as a whole before `SCHEME/ID` is considered.
+#[derive(Debug, Clone, PartialEq)]
+struct Account {
+    full_id: String, // as given, without currency
+    id: String,
+    schme_nm: String, // empty matches IBAN or any scheme
+    ccy: String,      // empty matches all currencies
+}
+
+impl Account {
+    fn parse(account: &str) -> Account {
+        let account = account.trim();
+        let (full_id, ccy) = account.rsplit_once(':').unwrap_or((account, ""));
+        let (schme_nm, id) = full_id.split_once('/').unwrap_or(("", full_id));
+        ensure!(!id.is_empty(), Input, "empty account id in {}", account);
+        Account {
+            full_id: full_id.to_string(),
+            id: id.to_string(),
+            schme_nm: schme_nm.to_string(),
+            ccy: ccy.to_uppercase(),
+        }
+    }
+
+    /// the statement belongs to this account (and currency if given)
+    fn matches(&self, stmt: &Stmt) -> bool {
+        let id_matches = if self.schme_nm.is_empty() {
+            stmt.iban == self.id || stmt.othr_id == self.id
+        } else {
+            // e.g. :25:10020030/1234567 of an MT940 statement
+            stmt.othr_id == self.full_id
+                || (stmt.othr_id == self.id && stmt.othr_schme_nm == self.schme_nm)
+        };
+        id_matches && (self.ccy.is_empty() || stmt.ccy == self.ccy)
+    }
+
+    fn name(&self) -> String {
+        let mut name = self.id.clone();
+        if !self.schme_nm.is_empty() {
+            name = format!("{}/{}", self.schme_nm, name);
+        }
+        if !self.ccy.is_empty() {
+            name = format!("{}:{}", name, self.ccy);
+        }
+        name
+    }
+}
+
+/// Public keys and signatures of the witnesses over the order data.
+/// Signatures, schemes and keys are matched by index, an empty signature means the witness
+/// did not sign. At least `threshold` distinct witnesses need a valid signature.
+#[derive(Debug)]
+struct WitnessSet {
+    keys: Vec<WitnessKey>,
+    schemes: Vec<WitnessScheme>,
+    signatures: Vec<Vec<u8>>,
+    threshold: usize,
+}
+
+/// Signature scheme a witness uses to sign the SHA-256 hash of the order data
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WitnessScheme {
+    /// RSA with PKCS#1 v1.5 padding and SHA-256 - EBICS A005
+    RsaPkcs1,
+    /// RSA-PSS with SHA-256 - EBICS A006
+    RsaPss,
+    /// ECDSA on P-256, signature over the SHA-256 hash (DER or fixed size)
+    EcdsaP256,
+    /// Ed25519, the SHA-256 hash is the signed message
+    Ed25519,
+}
+
+impl WitnessScheme {
+    fn parse(name: &str) -> WitnessScheme {
+        match name.trim().to_lowercase().as_str() {
+            "rsa-pkcs1" | "a005" => WitnessScheme::RsaPkcs1,
+            "rsa-pss" | "a006" => WitnessScheme::RsaPss,
+            "ecdsa-p256" => WitnessScheme::EcdsaP256,
+            "ed25519" => WitnessScheme::Ed25519,
+            _ => fail(HyperfridgeError::Input(format!(
+                "unknown witness signature scheme {}",
+                name
+            ))),
+        }
+    }
+
+    /// name as used in the commitment
+    fn name(&self) -> &'static str {
+        match self {
+            WitnessScheme::RsaPkcs1 => "rsa-pkcs1",
+            WitnessScheme::RsaPss => "rsa-pss",
+            WitnessScheme::EcdsaP256 => "ecdsa-p256",
+            WitnessScheme::Ed25519 => "ed25519",
+        }
+    }
+}
+
+/// Public key of a witness, the type depends on the signature scheme
+#[derive(Debug, Clone, PartialEq)]
+enum WitnessKey {
+    Rsa(RsaPublicKey),
+    EcdsaP256(P256VerifyingKey),
+    Ed25519(Ed25519VerifyingKey),
+}
+
+impl WitnessKey {
+    fn from_pem(pem: &str, scheme: WitnessScheme) -> WitnessKey {
+        match scheme {
+            WitnessScheme::RsaPkcs1 | WitnessScheme::RsaPss => {
+                WitnessKey::Rsa(RsaPublicKey::from_public_key_pem(pem).unwrap_or_else(|e| {
+                    fail(HyperfridgeError::Input(format!(
+                        "Failed to create RSA pub_witness_key {}",
+                        e
+                    )))
+                }))
+            }
+            WitnessScheme::EcdsaP256 => WitnessKey::EcdsaP256(
+                P256VerifyingKey::from_public_key_pem(pem).unwrap_or_else(|e| {
+                    fail(HyperfridgeError::Input(format!(
+                        "Failed to create P-256 pub_witness_key {}",
+                        e
+                    )))
+                }),
+            ),
+            WitnessScheme::Ed25519 => WitnessKey::Ed25519(
+                Ed25519VerifyingKey::from_public_key_pem(pem).unwrap_or_else(|e| {
+                    fail(HyperfridgeError::Input(format!(
+                        "Failed to create Ed25519 pub_witness_key {}",
+                        e
+                    )))
+                }),
+            ),
+        }
+    }
+
+    /// PEM of the key, independent of the formatting the host used
+    fn to_pem(&self) -> String {
+        match self {
+            WitnessKey::Rsa(key) => public_key_pem(key, "pub_witness"),
+            WitnessKey::EcdsaP256(key) => public_key_pem(key, "pub_witness"),
+            WitnessKey::Ed25519(key) => public_key_pem(key, "pub_witness"),
+        }
+    }
+
+    /// verify the signature over the hash of the order data
+    fn verify(
+        &self,
+        scheme: WitnessScheme,
+        order_data_hash: &[u8],
+        signature: &[u8],
+    ) -> Result<(), String> {
+        match (self, scheme) {
+            (WitnessKey::Rsa(key), WitnessScheme::RsaPkcs1) => key
+                .verify(Pkcs1v15Sign::new::<RsaSha256>(), order_data_hash, signature)
+                .map_err(|e| e.to_string()),
+            (WitnessKey::Rsa(key), WitnessScheme::RsaPss) => key
+                .verify(Pss::new::<RsaSha256>(), order_data_hash, signature)
+                .map_err(|e| e.to_string()),
+            (WitnessKey::EcdsaP256(key), WitnessScheme::EcdsaP256) => {
+                // KMS and HSMs usually return DER, raw r||s is accepted as well
+                let signature = p256::ecdsa::Signature::from_der(signature)
+                    .or_else(|_| p256::ecdsa::Signature::from_slice(signature))
+                    .map_err(|e| e.to_string())?;
+                key.verify_prehash(order_data_hash, &signature)
+                    .map_err(|e| e.to_string())
+            }
+            (WitnessKey::Ed25519(key), WitnessScheme::Ed25519) => {
+                let signature =
+                    ed25519_dalek::Signature::from_slice(signature).map_err(|e| e.to_string())?;
+                key.verify_strict(order_data_hash, &signature)
+                    .map_err(|e| e.to_string())
+            }
+            _ => Err(format!("key does not match scheme {}", scheme.name())),
+        }
+    }
+}
+
+/// Proof types, each built as its own guest image with its own image ID
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Proof {
+    /// balances, entries, payment status, proof of reserves and FX total
+    Full,
+    /// balances of the statements
+    Balance,
+    /// the account holder controls the account: a booked outgoing transfer carries `bind_to`,
+    /// no amounts
+    Binding,
+    /// balances, entry totals, proof of reserves and FX total
+    Aggregates,
+}
+
+impl Proof {
+    fn name(&self) -> &'static str {
+        match self {
+            Proof::Full => "full",
+            Proof::Balance => "balance",
+            Proof::Binding => "binding",
+            Proof::Aggregates => "aggregates",
+        }
+    }
+
+    fn balances(&self) -> bool {
+        *self != Proof::Binding
+    }
+
+    /// proof of reserves and total in a reporting currency
+    fn totals(&self) -> bool {
+        matches!(self, Proof::Full | Proof::Aggregates)
+    }
+}
+
+/// Input of the guest as written by the host, the same for every proof type
+struct Input {
+    // The inputs are the pre-processed XML files form EbicsResponse XML
+    // and keys necessary for  the proof.
+    signed_info_xml_c14n: String,
+    authenticated_xml_c14n: String,
+    signature_value_xml: String,
+    order_data_xml: String,
+    pub_bank_mod: String,
+    pub_bank_exp: String,
+    client_key_pem: String,
+    decrypted_tx_key_bin: Vec<u8>,
+    // accounts to prove as IBAN or IBAN:CCY
+    accounts: Vec<String>,
+    host_info: String,
+    // challenge of the verifier, binds the receipt to a specific verification request
+    challenge: String,
+    // on-chain identity (e.g. SS58/EVM address or DID) which may use the receipt;
+    // with bind_check it must be found in AddtlTxInf of an outgoing transfer
+    bind_to: String,
+    bind_check: bool,
+    witness_signatures_hex: Vec<String>,
+    pub_witness_pems: Vec<String>,
+    witness_schemes: Vec<String>,
+    witness_threshold: u32,
+    // bank transaction codes of the entries in the in- and outflows, empty for all
+    tx_codes: Vec<String>,
+    // selective disclosure of the committed fields, empty to commit all in cleartext
+    policy: String,
+    // total of the closing balances in the reporting currency, with FX rates signed by
+    // the rate provider - empty reporting currency for no conversion
+    reporting_ccy: String,
+    fx_rates: String,
+    fx_signature_hex: String,
+    pub_fx_pem: String,
+    fx_scheme: String,
+    // proof of reserves: further EBICS responses, e.g. of other banks, and the date of the
+    // closing balances - empty date for no reserves
+    reserves_responses: Vec<ResponseInput>,
+    reserves_date: String,
+    liabilities: Vec<String>,
+    flags: String,
+}
+
+impl Input {
+    /// reads the input from the host/main.rs, fields in the order the host writes them
+    fn read() -> Input {
+        Input {
+            signed_info_xml_c14n: env::read(),
+            authenticated_xml_c14n: env::read(),
+            signature_value_xml: env::read(),
+            order_data_xml: env::read(),
+            pub_bank_mod: env::read(),
+            pub_bank_exp: env::read(),
+            client_key_pem: env::read(),
+            decrypted_tx_key_bin: env::read(),
+            accounts: env::read(),
+            host_info: env::read(),
+            challenge: env::read(),
+            bind_to: env::read(),
+            bind_check: env::read(),
+            witness_signatures_hex: env::read(),
+            pub_witness_pems: env::read(),
+            witness_schemes: env::read(),
+            witness_threshold: env::read(),
+            tx_codes: env::read(),
+            policy: env::read(),
+            reporting_ccy: env::read(),
+            fx_rates: env::read(),
+            fx_signature_hex: env::read(),
+            pub_fx_pem: env::read(),
+            fx_scheme: env::read(),
+            reserves_responses: env::read(),
+            reserves_date: env::read(),
+            liabilities: env::read(),
+            flags: env::read(),
+        }
+    }
+}
+
+/// A verified EBICS response with the statements of the requested accounts
+struct Verified {
+    proof: Proof,
+    request: Request,
+    documents: Vec<Document>,
+    payments: Vec<pain002::PaymentStatus>,
+    pub_bank: RsaPublicKey,
+    client_key: RsaPrivateKey,
+    accounts: Vec<Account>,
+    witnesses: WitnessSet,
+    policy: Policy,
+    bind_check: bool,
+}
+
+/// Fields of the commitment which only some proof types compute
+#[derive(Default)]
+struct ProofFields {
+    tx_codes: Option<String>,
+    payments: Option<String>,
+    reserves: Option<String>, // JSON, null without proof of reserves
+    fx: Option<String>,       // JSON, null without FX total
+}
+
+/// A file of the order data, parsed with the formats the proof type accepts
+enum OrderFile {
+    Statements(Vec<Document>),
+    Payments(Vec<pain002::PaymentStatus>),
+}
+
+/// Full proof: balances, entries, payment status, proof of reserves and FX total
+pub fn prove_full() {
+    let input = Input::read();
+    let mut verified = verify(Proof::Full, &input, parse_statements_or_payments);
+    check_entries(&verified.documents);
+    let (reserves, fx) = totals(&input, &mut verified, parse_statements_or_payments);
+    let tx_codes = TxCodeFilter::parse(&input.tx_codes);
+    let payments = verified
+        .payments
+        .iter()
+        .map(|payment| payment_commitment(payment, &verified.policy))
+        .collect::<Vec<String>>();
+    let fields = ProofFields {
+        tx_codes: verified.policy.strings("tx_codes", &tx_codes.codes),
+        payments: Some(format!("[{}]", payments.join(","))),
+        reserves: Some(reserves),
+        fx: Some(fx),
+    };
+    commit(&input, verified, fields, &|stmt, policy| {
+        entry_commitments(stmt, &tx_codes, policy)
+    });
+}
+
+/// Balance proof: balances of the statements
+pub fn prove_balance() {
+    let input = Input::read();
+    let verified = verify(Proof::Balance, &input, parse_statements);
+    commit(&input, verified, ProofFields::default(), &|_, _| Vec::new());
+}
+
+/// Binding proof: a booked outgoing transfer from the account carries `bind_to`, no amounts
+pub fn prove_binding() {
+    let input = Input::read();
+    let verified = verify(Proof::Binding, &input, parse_statements);
+    commit(&input, verified, ProofFields::default(), &|_, _| Vec::new());
+}
+
+/// Aggregates proof: balances, entry totals, proof of reserves and FX total
+pub fn prove_aggregates() {
+    let input = Input::read();
+    let mut verified = verify(Proof::Aggregates, &input, parse_statements);
+    check_entries(&verified.documents);
+    let (reserves, fx) = totals(&input, &mut verified, parse_statements);
+    let tx_codes = TxCodeFilter::parse(&input.tx_codes);
+    let fields = ProofFields {
+        tx_codes: verified.policy.strings("tx_codes", &tx_codes.codes),
+        reserves: Some(reserves),
+        fx: Some(fx),
+        ..Default::default()
+    };
+    commit(&input, verified, fields, &|stmt, policy| {
+        entry_commitments(stmt, &tx_codes, policy)
+    });
+}
+
+/// camt53 or MT940 statements, accepted by every proof type
+fn parse_statements(content: &str) -> OrderFile {
+    // STA/VMK deliver SWIFT MT940/MT942 instead of camt53
+    if mt940::is_mt940(content) {
+        OrderFile::Statements(mt940::parse_mt940(content))
+    } else {
+        OrderFile::Statements(vec![
+            parse_camt53(content).unwrap_or_else(|e| fail(e.into()))
+        ])
+    }
+}
+
+/// statements, or the status report for uploaded payments (e.g. Z01 or HAC) of the full proof
+fn parse_statements_or_payments(content: &str) -> OrderFile {
+    if pain002::is_pain002(content) {
+        OrderFile::Payments(pain002::parse_pain002(content))
+    } else {
+        parse_statements(content)
+    }
+}
+
+/// Reads keys, accounts, witnesses and policy and verifies the EBICS response, shared by all
+/// proof types. If signatures are missing or invalid, this will fail with a HyperfridgeError.
+fn verify(proof: Proof, input: &Input, parse: fn(&str) -> OrderFile) -> Verified {
+    // process flags coming from the host, e.g. verbose
+    set_flags(&input.flags);
+    print_verbose!("proof {}", proof.name());
+    ensure!(
+        proof.totals()
+            || (input.reserves_responses.is_empty()
+                && input.reserves_date.is_empty()
+                && input.reporting_ccy.is_empty()),
+        Input,
+        "proof of reserves and FX total need the full or aggregates image, not {}",
+        proof.name()
+    );
+    // convert input to key objects
+    let pub_bank = bank_key(&input.pub_bank_mod, &input.pub_bank_exp);
+    print_verbose!("pub_bank {} bit", pub_bank.n().bits());
+    let client_key = RsaPrivateKey::from_pkcs8_pem(&input.client_key_pem).unwrap_or_else(|e| {
+        fail(HyperfridgeError::Input(format!(
+            "Failed to create client_key_pem in main {}",
+            e
+        )))
+    });
+    print_verbose!("client_key {} bit", client_key.n().bits());
+
+    let accounts: Vec<Account> = input
+        .accounts
+        .iter()
+        .map(|account| Account::parse(account))
+        .collect();
+    let policy = Policy::parse(&input.policy);
+    ensure!(
+        !accounts.is_empty(),
+        Input,
+        "at least one account is required"
+    );
+
+    let schemes: Vec<WitnessScheme> = input
+        .witness_schemes
+        .iter()
+        .map(|name| WitnessScheme::parse(name))
+        .collect();
+    ensure!(
+        schemes.len() == input.pub_witness_pems.len(),
+        Input,
+        "each witness key needs a signature scheme"
+    );
+    let witnesses = WitnessSet {
+        keys: input
+            .pub_witness_pems
+            .iter()
+            .zip(schemes.iter())
+            .map(|(pem, scheme)| WitnessKey::from_pem(pem, *scheme))
+            .collect(),
+        schemes,
+        signatures: witness_signatures(&input.witness_signatures_hex),
+        threshold: input.witness_threshold as usize,
+    };
+
+    // do the actual work
+    // it processes the private inputs and XML documents to check
+    // consistency and correctness of the data.
+    let (request, documents, payments) = load(
+        &input.authenticated_xml_c14n,
+        &input.signed_info_xml_c14n,
+        &input.signature_value_xml,
+        &input.order_data_xml,
+        &pub_bank,
+        &client_key,
+        &input.decrypted_tx_key_bin,
+        &accounts,
+        &witnesses,
+        parse,
+    );
+    Verified {
+        proof,
+        request,
+        documents,
+        payments,
+        pub_bank,
+        client_key,
+        accounts,
+        witnesses,
+        policy,
+        // the binding proof is the bind check
+        bind_check: input.bind_check || proof == Proof::Binding,
+    }
+}
+
+/// batch bookings need to be consistent with their transaction details, checked by the
+/// proof types which commit entries
+fn check_entries(documents: &[Document]) {
+    documents
+        .iter()
+        .flat_map(|document| document.stmts.iter())
+        .flat_map(|stmt| stmt.ntries.iter())
+        .for_each(check_batch);
+}
+
+/// entry summary and totals of a statement - the entries need to match <TxsSummry>
+fn entry_commitments(stmt: &Stmt, tx_codes: &TxCodeFilter, policy: &Policy) -> Vec<Option<String>> {
+    let totals = check_txs_summry(stmt);
+    vec![
+        policy.json(
+            "stmts.ntries",
+            &EntrySummary::of(stmt, tx_codes).commitment(),
+        ),
+        policy.json(
+            "stmts.txs_summry",
+            &totals.commitment(stmt.txs_summry.is_some()),
+        ),
+    ]
+}
+
+/// Proof of reserves over the further responses and the total in the reporting currency as
+/// JSON, `null` if not requested. The statements of the further responses are added to the
+/// verified ones.
+fn totals(
+    input: &Input,
+    verified: &mut Verified,
+    parse: fn(&str) -> OrderFile,
+) -> (String, String) {
+    let policy = &verified.policy;
+    // each further response is verified on its own, with its bank key and client key,
+    // the witnesses sign the order data of each response
+    let mut banks = vec![bank_commitment(
+        &verified.pub_bank,
+        &verified.request,
+        policy,
+    )];
+    for (
+        signed_info_xml_c14n,
+        authenticated_xml_c14n,
+        signature_value_xml,
+        order_data_xml,
+        pub_bank_mod,
+        pub_bank_exp,
+        client_key_pem,
+        decrypted_tx_key_bin,
+        witness_signatures_hex,
+    ) in input.reserves_responses.iter()
+    {
+        let pub_bank = bank_key(pub_bank_mod, pub_bank_exp);
+        let client_key = RsaPrivateKey::from_pkcs8_pem(client_key_pem).unwrap_or_else(|e| {
+            fail(HyperfridgeError::Input(format!(
+                "Failed to create client_key_pem of further response {}",
+                e
+            )))
+        });
+        let witnesses = WitnessSet {
+            keys: verified.witnesses.keys.clone(),
+            schemes: verified.witnesses.schemes.clone(),
+            signatures: witness_signatures(witness_signatures_hex),
+            threshold: verified.witnesses.threshold,
+        };
+        let (request, more_documents, _) = load(
+            authenticated_xml_c14n,
+            signed_info_xml_c14n,
+            signature_value_xml,
+            order_data_xml,
+            &pub_bank,
+            &client_key,
+            decrypted_tx_key_bin,
+            &verified.accounts,
+            &witnesses,
+            parse,
+        );
+        check_entries(&more_documents);
+        banks.push(bank_commitment(&pub_bank, &request, policy));
+        verified.documents.extend(more_documents);
+    }
+    let reserves = if input.reserves_date.is_empty() {
+        "null".to_string()
+    } else {
+        reserves::Reserves::of(
+            &verified.documents,
+            &input.reserves_date,
+            &input.liabilities,
+        )
+        .commitment(&banks)
+    };
+
+    let fx_total = if input.reporting_ccy.is_empty() {
+        "null".to_string()
+    } else {
+        let rates = fx::RateSet::parse(&input.fx_rates);
+        if !input.fx_rates.trim().is_empty() {
+            let scheme = WitnessScheme::parse(&input.fx_scheme);
+            let signature = Vec::from_hex(input.fx_signature_hex.trim().replace([' ', '\n'], ""))
+                .unwrap_or_else(|e| {
+                    fail(HyperfridgeError::Input(format!(
+                        "Failed to parse hexadecimal string fx_signature_hex {}",
+                        e
+                    )))
+                });
+            rates.verify(
+                &WitnessKey::from_pem(&input.pub_fx_pem, scheme),
+                scheme,
+                &signature,
+            );
+        }
+        fx::FxTotal::of(
+            &verified.documents,
+            &rates,
+            &input.reporting_ccy,
+            &input.reserves_date,
+        )
+        .commitment(&input.pub_fx_pem)
+    };
+    (reserves, fx_total)
+}
+
+/// Commits the statements, keys and witnesses of the verified response together with the
+/// fields of the proof type. `stmt_fields` are the fields of a statement beyond its account,
+/// dates and balances.
+fn commit(
+    input: &Input,
+    verified: Verified,
+    fields: ProofFields,
+    stmt_fields: &dyn Fn(&Stmt, &Policy) -> Vec<Option<String>>,
+) {
+    let Verified {
+        proof,
+        request,
+        documents,
+        payments,
+        pub_bank,
+        client_key,
+        accounts,
+        witnesses,
+        policy,
+        bind_check,
+    } = verified;
+
+    if bind_check {
+        find_binding(&documents, &input.bind_to);
+    }
+
+    print_verbose!(" Cycle count {}k", (env::cycle_count()) / 1000);
+
+    // public committed data, that is what we want to prove
+    // we only add data what we decide is OK to be public,
+    // what that is depends on use cases and is decided by the disclosure policy.
+    let mut commitments = Vec::new();
+
+    // An EbicsResponse can have multiple camt53 files, each with multiple transactions.
+    // Each Camt53 file is a day's worth of transactions and an offial final state similar
+    // to a confirmed block in a blockchain ledger.
+    // A document may contain statements for several accounts and currency sub-accounts,
+    // we commit one statement block per account and day.
+    for document in documents.iter() {
+        for stmt in document.stmts.iter() {
+            // we add the commitment for the daily statement as Json Object,
+            // each field as decided by the disclosure policy
+            let balances = stmt
+                .balances
+                .iter()
+                .map(balance_commitment)
+                .collect::<Vec<String>>()
+                .join(",");
+            let mut stmt_commitment = vec![
+                policy.string("stmts.msg_version", &document.msg_version),
+                policy.string("stmts.iban", &stmt.iban),
+                policy.string("stmts.othr_id", &stmt.othr_id),
+                policy.string("stmts.othr_schme_nm", &stmt.othr_schme_nm),
+                policy.string("stmts.acct_ccy", &stmt.ccy),
+                policy.string("stmts.elctrnc_seq_nb", &stmt.elctrnc_seq_nb),
+                policy.string("stmts.fr_dt_tm", &stmt.fr_dt_tm),
+                policy.string("stmts.to_dt_tm", &stmt.to_dt_tm),
+                policy
+                    .json("stmts.balances", &format!("[{}]", balances))
+                    .filter(|_| proof.balances()),
+            ];
+            stmt_commitment.extend(stmt_fields(stmt, &policy));
+            commitments.push(json_object(stmt_commitment));
+        }
+    }
+
+    // every requested account needs at least one statement, unless we prove payment status
+    for account in accounts.iter().filter(|_| payments.is_empty()) {
+        ensure!(
+            documents
+                .iter()
+                .flat_map(|document| document.stmts.iter())
+                .any(|stmt| account.matches(stmt)),
+            IbanNotFound,
+            "no statement found for account {}",
+            account.name()
+        );
+    }
+    let accounts = accounts
+        .iter()
+        .map(|account| account.name())
+        .collect::<Vec<String>>();
+
+    // we add the commitment for the public key of the bank and the client
+    let pub_bank_pem = public_key_pem(&pub_bank, "pub_bank");
+    let pub_client_pem = public_key_pem(&RsaPublicKey::from(&client_key), "client");
+
+    // the witness keys as given by the host, plus a hash over the set which does not
+    // depend on the order of the keys
+    let witness_schemes = witnesses
+        .schemes
+        .iter()
+        .map(|scheme| format!("\"{}\"", scheme.name()))
+        .collect::<Vec<String>>();
+
+    // Lets wrap all the commitment of daily statements
+    // into a single commitment for the receipt.
+    // The hostinfo, the proof type and the hash of the disclosure policy are always in cleartext.
+    let final_commitment = json_object(vec![
+        Some(format!(
+            "\"hostinfo\":\"{}\"",
+            escape_json(&input.host_info)
+        )),
+        Some(format!("\"proof\":\"{}\"", proof.name())),
+        Some(format!("\"policy_hash\":\"{}\"", policy.hash())),
+        policy.string("challenge", &input.challenge),
+        policy.string("bind_to", &input.bind_to),
+        policy.json("bind_checked", &bind_check.to_string()),
+        policy.json(
+            "include_pending",
+            &INCLUDE_PENDING.load(Ordering::Relaxed).to_string(),
+        ),
+        fields.tx_codes,
+        policy.string("order_type", &request.order_type),
+        policy.string("bank_timestamp", &request.bank_timestamp),
+        policy.strings("accounts", &accounts),
+        policy.string("pub_bank_pem", &pub_bank_pem),
+        policy.strings("pub_witness_pems", &input.pub_witness_pems),
+        policy.json(
+            "witness_schemes",
+            &format!("[{}]", witness_schemes.join(",")),
+        ),
+        policy.json(
+            "witness_keys_hash",
+            &format!("\"{}\"", witness_keys_hash(&witnesses)),
+        ),
+        policy.json("witness_threshold", &witnesses.threshold.to_string()),
+        policy.string("pub_client_pem", &pub_client_pem),
+        policy.json("stmts", &format!("[{}]", commitments.join(","))),
+        fields
+            .payments
+            .and_then(|payments| policy.json("payments", &payments)),
+        policy.json("reserves", fields.reserves.as_deref().unwrap_or("null")),
+        policy.json("fx", fields.fx.as_deref().unwrap_or("null")),
+    ]);
+    print_verbose!("Commitment for receipt: {}", &final_commitment);
+    // r0vm commit, this is the final output of the proof
+    env::commit(&final_commitment);
+}
+
+/// A further EBICS response as given by the host: SignedInfo, authenticated, SignatureValue,
+/// OrderData, modulus and exponent of the bank key, client key, decrypted transaction key and
+/// the witness signatures over its order data
+type ResponseInput = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    Vec<u8>,
+    Vec<String>,
+);
+
+/// public key of the bank from modulus and exponent as decimal numbers
+fn bank_key(pub_bank_mod: &str, pub_bank_exp: &str) -> RsaPublicKey {
+    let exp: BigUint = BigUint::parse_bytes(pub_bank_exp.as_bytes(), 10).unwrap_or_else(|| {
+        fail(HyperfridgeError::Input(
+            "error parsing EXP of public bank key".to_string(),
+        ))
+    });
+    let modu: BigUint = BigUint::parse_bytes(pub_bank_mod.as_bytes(), 10).unwrap_or_else(|| {
+        fail(HyperfridgeError::Input(
+            "error parsing MODULUS of public bank key".to_string(),
+        ))
+    });
+
+    // U256, use crypto_bigint::U256; does not work with RsaPublicKey
+    // let exp = U256::from_be_hex(&pub_bank_exp);
+    // let modu = U256::from_be_hex(&pub_bank_mod);
+
+    RsaPublicKey::new(modu, exp).unwrap_or_else(|e| {
+        fail(HyperfridgeError::Input(format!(
+            "Failed to create public key in main {}",
+            e
+        )))
+    })
+}
+
+/// witness signatures as hex, an empty string means the witness did not sign
+fn witness_signatures(witness_signatures_hex: &[String]) -> Vec<Vec<u8>> {
+    witness_signatures_hex
+        .iter()
+        .map(|hex| {
+            Vec::from_hex(hex.trim().replace([' ', '\n'], "")).unwrap_or_else(|e| {
+                fail(HyperfridgeError::Input(format!(
+                    "Failed to parse hexadecimal string witness_signature_hex {}",
+                    e
+                )))
+            })
+        })
+        .collect()
+}
+
+/// bank of a response for the proof of reserves: fingerprint of the bank key and
+/// the authenticated timestamp of the response
+fn bank_commitment(pub_bank: &RsaPublicKey, request: &Request, policy: &Policy) -> String {
+    let pub_bank_pem = public_key_pem(pub_bank, "pub_bank");
+    json_object(vec![
+        policy.string(
+            "reserves.pub_bank_fingerprint",
+            &policy::fingerprint(&pub_bank_pem),
+        ),
+        Some(format!(
+            "\"bank_timestamp\":\"{}\"",
+            escape_json(&request.bank_timestamp)
+        )),
+        Some(format!(
+            "\"order_type\":\"{}\"",
+            escape_json(&request.order_type)
+        )),
+    ])
+}
+
+/// PEM of a public key for the commitment, fails with `Input` if it can not be encoded
+fn public_key_pem(key: &impl EncodePublicKey, name: &str) -> String {
+    key.to_public_key_pem(LineEnding::LF).unwrap_or_else(|e| {
+        fail(HyperfridgeError::Input(format!(
+            "error encoding {} into pem {}",
+            name, e
+        )))
+    })
+}
+
+/// set the verbose flag
+fn set_flags(flags: &str) {
+    if flags.contains("verbose") {
+        VERBOSE.store(true, Ordering::Relaxed);
+    }
+    if flags.contains("include_pending") {
+        INCLUDE_PENDING.store(true, Ordering::Relaxed);
+    }
+}
+
+/// escape a string so it can be placed in a JSON string of the commitment
+fn escape_json(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// all balances of a statement are committed - OPBD, CLBD, CLAV, ITBD, FWAV, PRCD..
+/// next to the amount as in the camt53 file, the signed amount in minor units is committed
+fn balance_commitment(balance: &Balance) -> String {
+    format!(
+        "{{\"cd\":\"{}\",\"amt\":\"{}\",\"amt_minor\":{},\"ccy\":\"{}\",\"ccy_exp\":{},\"cdt_dbt_ind\":\"{}\",\"dt\":\"{}\"}}",
+        escape_json(&balance.cd),
+        escape_json(&balance.amt),
+        to_minor_units(&balance.amt, &balance.ccy, &balance.cdt_dbt_ind),
+        escape_json(&balance.ccy),
+        currency_exponent(&balance.ccy),
+        escape_json(&balance.cdt_dbt_ind),
+        escape_json(&balance.dt),
+    )
+}
+
+/// Closing booked balance (CLBD) of an account, the latest of all statements
+#[derive(Debug, Clone, PartialEq)]
+struct ClosingBalance {
+    account: String, // IBAN or other id
+    ccy: String,
+    dt: String,
+    amt_minor: i64,
+}
+
+/// latest closing booked balance per account and currency, only balances dated up to
+/// `until` (YYYY-MM-DD) if given
+fn closing_balances(documents: &[Document], until: Option<&str>) -> Vec<ClosingBalance> {
+    let mut closing: Vec<ClosingBalance> = Vec::new();
+    for stmt in documents.iter().flat_map(|document| document.stmts.iter()) {
+        let account = if stmt.iban.is_empty() {
+            &stmt.othr_id
+        } else {
+            &stmt.iban
+        };
+        for balance in stmt.balances.iter().filter(|balance| balance.cd == "CLBD") {
+            let date = balance.dt.get(..10).unwrap_or(&balance.dt);
+            if until.is_some_and(|until| date > until) {
+                continue;
+            }
+            let amt_minor = to_minor_units(&balance.amt, &balance.ccy, &balance.cdt_dbt_ind);
+            match closing
+                .iter_mut()
+                .find(|latest| latest.account == *account && latest.ccy == balance.ccy)
+            {
+                Some(latest) if latest.dt <= balance.dt => {
+                    latest.dt = balance.dt.clone();
+                    latest.amt_minor = amt_minor;
+                }
+                Some(_) => {}
+                None => closing.push(ClosingBalance {
+                    account: account.clone(),
+                    ccy: balance.ccy.clone(),
+                    dt: balance.dt.clone(),
+                    amt_minor,
+                }),
+            }
+        }
+    }
+    closing
+}
+
+/// status of an uploaded payment information block, or of the group of the pain.001
+fn payment_commitment(payment: &pain002::PaymentStatus, policy: &Policy) -> String {
+    json_object(vec![
+        policy.string("payments.level", &payment.level),
+        policy.string("payments.msg_id", &payment.msg_id),
+        policy.string("payments.orgnl_msg_id", &payment.orgnl_msg_id),
+        policy.string("payments.pmt_inf_id", &payment.pmt_inf_id),
+        policy.string("payments.orgnl_nb_of_txs", &payment.orgnl_nb_of_txs),
+        policy.string("payments.ctrl_sum", &payment.ctrl_sum),
+        policy.json("payments.listed_txs", &payment.listed_txs.to_string()),
+        policy.json(
+            "payments.listed_amt_minor",
+            &payment.listed_amt_minor.to_string(),
+        ),
+        policy.string("payments.ccy", &payment.ccy),
+        policy.string("payments.sts", &payment.sts),
+    ])
+}
+
+/// number of minor units of a currency as of ISO 4217, e.g. 2 for CHF (Rappen)
+fn currency_exponent(ccy: &str) -> u32 {
+    ensure!(
+        ccy.len() == 3 && ccy.chars().all(|c| c.is_ascii_uppercase()),
+        Parse,
+        "invalid currency code '{}'",
+        ccy
+    );
+    match ccy {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        "CLF" | "UYW" => 4,
+        _ => 2,
+    }
+}
+
+/// converts an amount of a camt53 file to a signed integer in minor units of the currency,
+/// e.g. "31709.14" CHF DBIT is -3170914. Fails if the amount is not a valid decimal, has more
+/// fraction digits than the currency or does not fit into i64.
+fn to_minor_units(amt: &str, ccy: &str, cdt_dbt_ind: &str) -> i64 {
+    let exponent = currency_exponent(ccy);
+    let (units, fraction) = amt.split_once('.').unwrap_or((amt, ""));
+    ensure!(
+        !units.is_empty()
+            && units.chars().all(|c| c.is_ascii_digit())
+            && fraction.chars().all(|c| c.is_ascii_digit()),
+        Parse,
+        "invalid amount '{}'",
+        amt
+    );
+    ensure!(
+        fraction.len() <= exponent as usize,
+        Parse,
+        "amount {} has more fraction digits than {} allows ({})",
+        amt,
+        ccy,
+        exponent
+    );
+    let minor_units = format!("{}{:0<width$}", units, fraction, width = exponent as usize)
+        .parse::<i64>()
+        .unwrap_or_else(|_| {
+            fail(HyperfridgeError::Parse(format!(
+                "amount {} is too large",
+                amt
+            )))
+        });
+    match cdt_dbt_ind {
+        "CRDT" => minor_units,
+        "DBIT" => -minor_units,
+        _ => fail(HyperfridgeError::Parse(format!(
+            "invalid credit debit indicator '{}' for amount {}",
+            cdt_dbt_ind, amt
+        ))),
+    }
+}
+
+/// adds up minor units, fails with `Parse` if the sum does not fit
+fn add_minor(sum: i64, amount: i64) -> i64 {
+    sum.checked_add(amount).unwrap_or_else(|| {
+        fail(HyperfridgeError::Parse(
+            "sum of the amounts is too large".to_string(),
+        ))
+    })
+}
+
+/// SHA-256 (hex) over the sorted schemes and PEMs of the witness keys - identifies the
+/// key set independent of the order in which the host provided the keys. The keys are
+/// distinct, see `verify_witness_signatures`.
+fn witness_keys_hash(witnesses: &WitnessSet) -> String {
+    let mut pems: Vec<String> = witnesses
+        .keys
+        .iter()
+        .zip(witnesses.schemes.iter())
+        .map(|(key, scheme)| format!("{}\n{}", scheme.name(), key.to_pem()))
+        .collect();
+    pems.sort();
+    hex::encode(Impl::hash_bytes(pems.concat().as_bytes()).as_bytes())
+}
+
+/// Proof of account ownership: the account holder sends a micro-transfer from the
+/// proven account with `bind_to` in `AddtlTxInf`, or `AddtlNtryInf` of an entry without
+/// transaction details. Fails with `BindingNotProven` if no such booked debit is found.
+fn find_binding(documents: &[Document], bind_to: &str) {
+    ensure!(
+        !bind_to.is_empty(),
+        Input,
+        "bind check requires a bind_to value"
+    );
+    let found = documents
+        .iter()
+        .flat_map(|document| document.stmts.iter())
+        .flat_map(|stmt| stmt.ntries.iter())
+        .filter(|ntry| EntryCategory::of(ntry) == EntryCategory::Booked)
+        .flat_map(|ntry| ntry.transactions())
+        .any(|tx_dtls| {
+            tx_dtls.CdtDbtInd == "DBIT"
+                && tx_dtls
+                    .AddtlTxInf
+                    .as_deref()
+                    .is_some_and(|info| carries_binding(info, bind_to))
+        });
+    ensure!(
+        found,
+        BindingNotProven,
+        "no outgoing transfer with {} in AddtlTxInf found - binding not proven",
+        bind_to
+    );
+    print_verbose!(" found transfer binding the account to {}", bind_to);
+}
+
+/// `AddtlTxInf` carries `bind_to` as a whole: the complete text, a whitespace separated word
+/// or the part of a word after `:` (e.g. `ref:<bind_to>`). A prefix of the value is no binding.
+fn carries_binding(info: &str, bind_to: &str) -> bool {
+    info.trim() == bind_to
+        || info.split_whitespace().any(|word| {
+            word.strip_suffix(bind_to)
+                .is_some_and(|rest| rest.is_empty() || rest.ends_with(':'))
+        })
+}
+
+/// Calls all the steps necessary for the proof, the files of the order data are parsed with
+/// the formats of the proof type.
+#[allow(clippy::too_many_arguments)]
+fn load(
+    authenticated_xml_c14n: &str,
+    signed_info_xml_c14n: &str,
+    signature_value_xml: &str,
+    order_data_xml: &str,
+    pub_bank: &RsaPublicKey,
+    client_key: &RsaPrivateKey,
+    decrypted_tx_key: &[u8],
+    accounts: &[Account],
+    witnesses: &WitnessSet,
+    parse: fn(&str) -> OrderFile,
+) -> (Request, Vec<Document>, Vec<pain002::PaymentStatus>) {
+    // star is with 1586k
+    print_verbose!("   Cycle count start {}k", (env::cycle_count()) / 1000);
+
+    // convert the XML files to a structure and do first consistency checks
+    let request = parse_ebics_response(
+        authenticated_xml_c14n,
+        signed_info_xml_c14n,
+        signature_value_xml,
+        order_data_xml,
+    )
+    .unwrap_or_else(|e| fail(e.into()));
+    print_verbose!(
+        " >  Cycle count parse_ebics_response {}k",
+        (env::cycle_count()) / 1000
+    );
+    // cycle count 1864k (plus 3k)
+    // verify the signature of the bank
+    verify_bank_signature(pub_bank, &request).unwrap_or_else(|e| fail(e.into()));
+    print_verbose!(
+        "   Cycle count verify_bank_signature {}k",
+        (env::cycle_count()) / 1000
+    );
+
+    // cycle count 23336k (plus 10k)
+    // decrypt the transaction key which is used to decrypt the payload
+    let transaction_key = decrypt_transaction_key(&request, client_key, decrypted_tx_key)
+        .unwrap_or_else(|e| fail(e.into()));
+    print_verbose!(
+        "   Cycle count decrypt_transaction_key {}k",
+        (env::cycle_count()) / 1000
+    );
+    // cycle count 33979k (plus 10k)
+
+    // the witnesses sign the sha256 hash of the order data according to Ebics Standard
+    let order_data_sha = order_data_hash(&request).unwrap_or_else(|e| fail(e.into()));
+    print_verbose!(
+        "   Cycle count before pub_witness.verify( {}k",
+        (env::cycle_count()) / 1000
+    );
+    verify_witness_signatures(witnesses, &order_data_sha);
+    print_verbose!(
+        "   Cycle count after pub_witness.verify( {}k",
+        (env::cycle_count()) / 1000
+    );
+
+    // cycle count 35906k (plus 2k)
+    // decrypt the payload and add each XML document to order_data,
+    // where order[i]=filename, order[i+1]=filecontent
+    print_verbose!(" decrypting payload with transaction key");
+    let order_data =
+        decrypt_order_data(&request, &transaction_key).unwrap_or_else(|e| fail(e.into()));
+    print_verbose!(
+        "   Cycle count decrypt_order_data {}k",
+        (env::cycle_count()) / 1000
+    );
+
+    //let document=parse_camt53(std::str::from_utf8(&order_data[1].to_vec()).unwrap());
+    let mut pages = Vec::new();
+    let mut payments = Vec::new();
+
+    // parse the camt53 (or MT940) files
+    for (index, data) in order_data.iter().enumerate() {
+        // Process only odd indices because other indices are filenames
+        if index % 2 != 0 {
+            let content = std::str::from_utf8(data).unwrap_or_else(|e| {
+                fail(HyperfridgeError::Parse(format!(
+                    "order data file is not UTF-8 {}",
+                    e
+                )))
+            });
+            match parse(content) {
+                OrderFile::Statements(documents) => pages.extend(documents),
+                OrderFile::Payments(statuses) => payments.extend(statuses),
+            }
+            print_verbose!(
+                "   Cycle count for camt document {}k",
+                (env::cycle_count()) / 1000
+            );
+        } else {
+            print_verbose!(
+                " processing camt document {}",
+                String::from_utf8_lossy(&order_data[index])
+            );
+        }
+    }
+
+    // a statement may be split over several pages, each page is a camt53 file
+    let mut documents = merge_pages(pages);
+
+    // Retain only those statements which belong to one of the accounts
+    for document in documents.iter_mut() {
+        document
+            .stmts
+            .retain(|stmt| accounts.iter().any(|account| account.matches(stmt)));
+        if document.stmts.is_empty() {
+            print_verbose!(
+                " accounts not found, ignore camt document {}",
+                document.grp_hdr.msg_id
+            );
+        }
+    }
+    // Keep only documents with at least one matching statement
+    documents.retain(|document| !document.stmts.is_empty());
+
+    print_verbose!(
+        "   Cycle count parse_camt53 {}k",
+        (env::cycle_count()) / 1000
+    );
+    // cycle count 36330k (plus 1k)
+    (request, documents, payments)
+}
+
+/// Reassembles paginated camt53 messages (`<MsgPgntn>`): pages are grouped by `MsgId`, must be
+/// numbered 1..n and only the last page has `LastPgInd=true`. Statements which continue on the
+/// next page (same statement id, account and sequence number) are merged into one. Documents
+/// without pagination are returned as they are.
+fn merge_pages(pages: Vec<Document>) -> Vec<Document> {
+    let mut messages: Vec<Vec<Document>> = Vec::new();
+    for page in pages {
+        // documents without pagination are never grouped
+        match messages.iter_mut().find(|message| {
+            page.grp_hdr.pg_nb != 0
+                && message[0].grp_hdr.pg_nb != 0
+                && message[0].grp_hdr.msg_id == page.grp_hdr.msg_id
+        }) {
+            Some(message) => message.push(page),
+            None => messages.push(vec![page]),
+        }
+    }
+
+    let mut documents = Vec::new();
+    for mut message in messages {
+        if message.len() == 1 && message[0].grp_hdr.pg_nb == 0 {
+            documents.extend(message);
+            continue;
+        }
+        message.sort_by_key(|page| page.grp_hdr.pg_nb);
+        let msg_id = message[0].grp_hdr.msg_id.clone();
+        for (index, page) in message.iter().enumerate() {
+            ensure!(
+                page.grp_hdr.pg_nb as usize == index + 1,
+                Parse,
+                "page {} of camt53 message {} is missing",
+                index + 1,
+                msg_id
+            );
+            ensure!(
+                page.grp_hdr.last_pg_ind == (index + 1 == message.len()),
+                Parse,
+                "camt53 message {} is incomplete, page {} of {} has LastPgInd {}",
+                msg_id,
+                index + 1,
+                message.len(),
+                page.grp_hdr.last_pg_ind
+            );
+        }
+        print_verbose!(
+            " merging {} pages of camt53 message {}",
+            message.len(),
+            msg_id
+        );
+
+        let mut pages = message.into_iter();
+        let mut document = pages.next().expect("message has at least one page");
+        for page in pages {
+            for stmt in page.stmts {
+                match document.stmts.iter_mut().find(|merged| {
+                    merged.id == stmt.id
+                        && merged.iban == stmt.iban
+                        && merged.othr_id == stmt.othr_id
+                        && merged.elctrnc_seq_nb == stmt.elctrnc_seq_nb
+                }) {
+                    Some(merged) => {
+                        merged.balances.extend(stmt.balances);
+                        merged.ntries.extend(stmt.ntries);
+                        if merged.txs_summry.is_none() {
+                            merged.txs_summry = stmt.txs_summry;
+                        }
+                    }
+                    None => document.stmts.push(stmt),
+                }
+            }
+        }
+        document.grp_hdr.last_pg_ind = true;
+        documents.push(document);
+    }
+    documents
+}
+
+/// Checks that at least `threshold` distinct witnesses signed the hash of the order data.
+/// A key which is given twice is only counted once, invalid or missing signatures
+/// are ignored as long as enough valid signatures remain.
+fn verify_witness_signatures(witnesses: &WitnessSet, order_data_hash: &[u8]) {
+    ensure!(
+        witnesses.keys.len() == witnesses.signatures.len(),
+        Input,
+        "each witness key needs a (possibly empty) signature"
+    );
+    ensure!(
+        witnesses.keys.len() == witnesses.schemes.len(),
+        Input,
+        "each witness key needs a signature scheme"
+    );
+    ensure!(
+        witnesses.threshold > 0 && witnesses.threshold <= witnesses.keys.len(),
+        Input,
+        "witness threshold {} needs to be between 1 and the number of witness keys {}",
+        witnesses.threshold,
+        witnesses.keys.len()
+    );
+    // a key counts once towards the threshold and once in witness_keys_hash, whatever
+    // the scheme it is listed with
+    let pems: Vec<String> = witnesses.keys.iter().map(WitnessKey::to_pem).collect();
+    for (index, pem) in pems.iter().enumerate() {
+        ensure!(
+            !pems[..index].contains(pem),
+            Input,
+            "witness key {} is listed more than once",
+            index
+        );
+    }
+
+    let mut signed_by: Vec<&WitnessKey> = Vec::new();
+    for (index, ((key, scheme), signature)) in witnesses
+        .keys
+        .iter()
+        .zip(witnesses.schemes.iter())
+        .zip(witnesses.signatures.iter())
+        .enumerate()
+    {
+        if signature.is_empty() {
+            continue;
+        }
+        match key.verify(*scheme, order_data_hash, signature) {
+            Ok(_) => {
+                print_verbose!(" Order Data is verified by witness {}", index);
+                signed_by.push(key);
+            }
+            Err(e) => print_verbose!(" witness {} signature invalid {:?}", index, e),
+        }
+    }
+
+    ensure!(
+        signed_by.len() >= witnesses.threshold,
+        WitnessInvalid,
+        "Order Data Signature could not be verified - {} of {} required witness signatures are valid",
+        signed_by.len(),
+        witnesses.threshold
+    );
+}
@@ -0,0 +1,272 @@
+//! SWIFT MT940 (end of day statement) and MT942 (interim report) as delivered by EBICS
+//! order types STA, VMK. Each message is mapped to a `Document` with one `Stmt`, so the
+//! commitment has the same shape as for camt53:
+//!
+//! - `:20:` reference as message id, `:25:` account, `:28C:` statement number
+//! - `:60F:`/`:60M:` opening, `:62F:`/`:62M:` closing, `:64:` available, `:65:` forward balance
+//! - `:61:` entries with the following `:86:` as additional information, the transaction type
+//!   (e.g. `NTRF`) as proprietary bank transaction code
+
+use crate::{fail, Balance, BkTxCd, Document, HyperfridgeError, Ntry, Stmt, TxDtls};
+
+/// true if the order data is an MT940/MT942 file and not XML
+pub(crate) fn is_mt940(content: &str) -> bool {
+    let content = content.trim_start_matches('\u{feff}').trim_start();
+    !content.starts_with('<') && (content.contains(":20:") && content.contains(":25:"))
+}
+
+/// parses all messages of an MT940/MT942 file, fails on malformed fields
+pub(crate) fn parse_mt940(content: &str) -> Vec<Document> {
+    split_messages(content)
+        .iter()
+        .map(|message| parse_message(message))
+        .filter(|document| !document.stmts.is_empty())
+        .collect()
+}
+
+/// messages are separated by a line with "-", optionally wrapped in SWIFT blocks {1:..}{4:..-}
+fn split_messages(content: &str) -> Vec<Vec<(String, String)>> {
+    let mut messages = Vec::new();
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        // block 4 holds the text of the message, header blocks are ignored
+        let line = match line.find("{4:") {
+            Some(pos) => &line[pos + 3..],
+            None => line,
+        };
+        if line.trim() == "-" || line.trim() == "-}" || line.trim_start().starts_with("-}") {
+            if !fields.is_empty() {
+                messages.push(std::mem::take(&mut fields));
+            }
+            continue;
+        }
+        match field_tag(line) {
+            Some((tag, value)) => fields.push((tag.to_string(), value.to_string())),
+            None => {
+                // continuation line of the previous field
+                if let Some((_, value)) = fields.last_mut() {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+        }
+    }
+    if !fields.is_empty() {
+        messages.push(fields);
+    }
+    messages
+}
+
+/// splits ":61:2311291129D0,05NTRF.." into ("61", "2311291129D0,05NTRF..")
+fn field_tag(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix(':')?;
+    let end = rest.find(':')?;
+    let tag = &rest[..end];
+    let valid = tag.is_ascii()
+        && (2..=3).contains(&tag.len())
+        && tag[..2].chars().all(|c| c.is_ascii_digit())
+        && tag[2..].chars().all(|c| c.is_ascii_uppercase());
+    if valid {
+        Some((tag, &rest[end + 1..]))
+    } else {
+        None
+    }
+}
+
+fn parse_message(fields: &[(String, String)]) -> Document {
+    let mut document = Document::default();
+    // MT942 has no opening balance but a floor limit (:34F:)
+    let is_mt942 = fields.iter().any(|(tag, _)| tag == "34F" || tag == "13D");
+    document.msg_version = if is_mt942 { "MT942" } else { "MT940" }.to_string();
+
+    let mut stmt = Stmt::default();
+    let mut ccy = String::new();
+    for (tag, value) in fields {
+        let value = value.trim();
+        match tag.as_str() {
+            "20" => document.grp_hdr.msg_id = value.to_string(),
+            "25" => {
+                // IBAN or bank code/account number for other accounts
+                let account = value.lines().next().unwrap_or_default().trim();
+                if looks_like_iban(account) {
+                    stmt.iban = account.to_string();
+                } else {
+                    stmt.othr_id = account.to_string();
+                }
+            }
+            // statement number/sequence number, e.g. 00247/001
+            "28C" | "28" => {
+                stmt.elctrnc_seq_nb = value
+                    .split('/')
+                    .next()
+                    .unwrap_or_default()
+                    .trim_start_matches('0')
+                    .to_string();
+            }
+            // date and time of an MT942, YYMMDDHHMM+offset
+            "13D" => {
+                ensure!(value.is_ascii(), Parse, "invalid MT942 date :13D:{}", value);
+                let date = to_iso_date(&value[..6.min(value.len())]);
+                stmt.fr_dt_tm = format!("{}T00:00:00", date);
+                stmt.to_dt_tm = format!("{}T00:00:00", date);
+                stmt.cre_dt_tm = date;
+            }
+            "34F" if ccy.is_empty() && value.len() >= 3 && value.is_ascii() => {
+                ccy = value[..3].to_string();
+            }
+            "60F" | "60M" | "62F" | "62M" | "64" | "65" => {
+                let balance = parse_balance(tag, value);
+                ccy = balance.ccy.clone();
+                match tag.as_str() {
+                    "60F" | "60M" => stmt.fr_dt_tm = format!("{}T00:00:00", balance.dt),
+                    "62F" | "62M" => stmt.to_dt_tm = format!("{}T00:00:00", balance.dt),
+                    _ => {}
+                }
+                stmt.balances.push(balance);
+            }
+            "61" => stmt.ntries.push(parse_entry(value, &ccy)),
+            // information to account owner, belongs to the preceding :61:
+            "86" => {
+                if let Some(tx_dtls) = stmt
+                    .ntries
+                    .last_mut()
+                    .and_then(|ntry| ntry.txDtls.last_mut())
+                {
+                    tx_dtls.AddtlTxInf = Some(value.replace('\n', ""));
+                }
+            }
+            _ => {}
+        }
+    }
+    if !stmt.iban.is_empty() || !stmt.othr_id.is_empty() {
+        document.stmts.push(stmt);
+    }
+    document
+}
+
+/// ":62F:C231129CHF31709,09" - credit/debit, date, currency and amount
+fn parse_balance(tag: &str, value: &str) -> Balance {
+    ensure!(
+        value.len() > 10 && value.is_ascii(),
+        Parse,
+        "invalid MT940 balance :{}:{}",
+        tag,
+        value
+    );
+    let cdt_dbt_ind = match &value[..1] {
+        "C" => "CRDT",
+        "D" => "DBIT",
+        _ => fail(HyperfridgeError::Parse(format!(
+            "invalid MT940 balance :{}:{}",
+            tag, value
+        ))),
+    };
+    Balance {
+        cd: match tag {
+            "60F" => "OPBD",
+            "60M" | "62M" => "ITBD",
+            "62F" => "CLBD",
+            "64" => "CLAV",
+            _ => "FWAV",
+        }
+        .to_string(),
+        dt: to_iso_date(&value[1..7]),
+        ccy: value[7..10].to_string(),
+        amt: to_decimal(&value[10..]),
+        cdt_dbt_ind: cdt_dbt_ind.to_string(),
+    }
+}
+
+/// ":61:2311291129D0,05NTRFNONREF//ref" - value date, optional entry date, mark, optional
+/// funds code, amount and transaction type
+fn parse_entry(value: &str, ccy: &str) -> Ntry {
+    let line = value.lines().next().unwrap_or_default();
+    ensure!(
+        line.len() > 7 && line.is_ascii(),
+        Parse,
+        "invalid MT940 entry :61:{}",
+        value
+    );
+    let mut rest = &line[6..];
+    // optional entry date MMDD
+    if rest.len() >= 4 && rest[..4].chars().all(|c| c.is_ascii_digit()) {
+        rest = &rest[4..];
+    }
+    // reversals book in the opposite direction
+    let (cdt_dbt_ind, mark_len, reversal) = if rest.starts_with("RC") {
+        ("DBIT", 2, true)
+    } else if rest.starts_with("RD") {
+        ("CRDT", 2, true)
+    } else if rest.starts_with('C') {
+        ("CRDT", 1, false)
+    } else if rest.starts_with('D') {
+        ("DBIT", 1, false)
+    } else {
+        fail(HyperfridgeError::Parse(format!(
+            "invalid debit/credit mark in MT940 entry :61:{}",
+            value
+        )))
+    };
+    rest = &rest[mark_len..];
+    // optional funds code, the third character of the currency
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        rest = &rest[1..];
+    }
+    let amount_len = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == ','))
+        .unwrap_or(rest.len());
+    let amt = to_decimal(&rest[..amount_len]);
+    // transaction type identification code, e.g. NTRF or NCHK, as proprietary bank transaction code
+    let tx_type = rest[amount_len..].get(..4).unwrap_or_default();
+
+    Ntry {
+        cdtDbtInd: cdt_dbt_ind.to_string(),
+        sts: "BOOK".to_string(),
+        rvslInd: reversal,
+        ccy: ccy.to_string(),
+        amt: amt.clone(),
+        bkTxCd: BkTxCd {
+            prtry: tx_type.to_string(),
+            ..Default::default()
+        },
+        txDtls: vec![TxDtls {
+            AmtCcy: ccy.to_string(),
+            AmtValue: amt,
+            CdtDbtInd: cdt_dbt_ind.to_string(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+/// "31709,09" to "31709.09" as in camt53
+fn to_decimal(amount: &str) -> String {
+    let amount = amount.trim();
+    ensure!(
+        !amount.is_empty() && amount.chars().all(|c| c.is_ascii_digit() || c == ','),
+        Parse,
+        "invalid MT940 amount {}",
+        amount
+    );
+    amount.trim_end_matches(',').replace(',', ".")
+}
+
+/// YYMMDD to YYYY-MM-DD
+fn to_iso_date(date: &str) -> String {
+    ensure!(
+        date.len() == 6 && date.chars().all(|c| c.is_ascii_digit()),
+        Parse,
+        "invalid MT940 date {}",
+        date
+    );
+    format!("20{}-{}-{}", &date[..2], &date[2..4], &date[4..6])
+}
+
+/// country code and check digits, the checksum is validated by the host
+fn looks_like_iban(account: &str) -> bool {
+    account.len() > 4
+        && account[..2].chars().all(|c| c.is_ascii_uppercase())
+        && account[2..4].chars().all(|c| c.is_ascii_digit())
+        && account.chars().all(|c| c.is_ascii_alphanumeric())
+}
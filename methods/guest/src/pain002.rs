@@ -0,0 +1,191 @@
+//! Customer payment status report (pain.002), downloaded for uploaded payment orders (pain.001)
+//! e.g. with EBICS order types Z01, PSR or HAC. As the report is part of the bank-signed order
+//! data, the receipt proves that a payment was accepted (or rejected) by the bank.
+
+use crate::{fail, path_ends_with, to_minor_units, HyperfridgeError};
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+const NAMESPACE: &str = "urn:iso:std:iso:20022:tech:xsd:pain.002.001.";
+const VERSIONS: [&str; 2] = ["03", "10"];
+
+/// Level of a status: a payment information block or, for reports without any, the whole group
+pub(crate) const LEVEL_PAYMENT: &str = "payment";
+pub(crate) const LEVEL_GROUP: &str = "group";
+
+/// Status of a payment information block (`OrgnlPmtInfAndSts`) of the original pain.001, or of
+/// the group (`OrgnlGrpInfAndSts`) if the report has no payment information blocks.
+/// `orgnl_nb_of_txs` and `ctrl_sum` are stated by the report, the `listed_` fields only cover
+/// the transactions the report happens to list (`TxInfAndSts`).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct PaymentStatus {
+    pub(crate) level: String,           // LEVEL_PAYMENT or LEVEL_GROUP
+    pub(crate) msg_id: String,          // MsgId of the status report
+    pub(crate) orgnl_msg_id: String,    // MsgId of the pain.001
+    pub(crate) pmt_inf_id: String,      // OrgnlPmtInfId, empty for the group
+    pub(crate) orgnl_nb_of_txs: String, // OrgnlNbOfTxs as in the report, may be empty
+    pub(crate) ctrl_sum: String,        // OrgnlCtrlSum as in the report, may be empty
+    pub(crate) listed_txs: usize,       // transactions listed in the report
+    pub(crate) listed_amt_minor: i64,   // sum of their instructed amounts in minor units
+    pub(crate) ccy: String,             // currency of the listed instructed amounts
+    pub(crate) sts: String,             // ACCP, ACSC, RJCT..
+}
+
+/// true if the order data is a pain.002 status report
+pub(crate) fn is_pain002(content: &str) -> bool {
+    document_namespace(content)
+        .and_then(|ns| ns.strip_prefix(NAMESPACE).map(|v| VERSIONS.contains(&v)))
+        .unwrap_or(false)
+}
+
+/// namespace of the root element, declared as xmlns or with the prefix of the root element
+fn document_namespace(content: &str) -> Option<&str> {
+    let mut root_prefix = None;
+    for token in Tokenizer::from(content) {
+        match token {
+            Ok(Token::ElementStart { prefix, .. }) => root_prefix = Some(prefix.as_str()),
+            Ok(Token::Attribute {
+                prefix,
+                local,
+                value,
+                ..
+            }) => {
+                let declares_root = match root_prefix {
+                    Some("") => prefix.is_empty() && local == "xmlns",
+                    Some(root) => prefix == "xmlns" && local == root,
+                    None => false,
+                };
+                if declares_root {
+                    return Some(value.as_str());
+                }
+            }
+            Ok(Token::ElementEnd { .. }) | Err(_) => return None,
+            Ok(_) => {}
+        }
+    }
+    None
+}
+
+/// parses a pain.002.001.03 or pain.002.001.10 status report
+pub(crate) fn parse_pain002(content: &str) -> Vec<PaymentStatus> {
+    let mut tag_stack: Vec<String> = Vec::new();
+    let mut statuses = Vec::new();
+    let mut current = PaymentStatus::default();
+    let mut msg_id = String::new();
+    let mut orgnl_msg_id = String::new();
+    let mut grp_sts = String::new();
+    let mut grp_nb_of_txs = String::new();
+    let mut grp_ctrl_sum = String::new();
+    let mut tx_sts: Vec<String> = Vec::new();
+    let mut instd_ccy = String::new();
+    let namespace = document_namespace(content);
+    ensure!(
+        namespace.is_some(),
+        Parse,
+        "pain.002 document without namespace"
+    );
+    ensure!(
+        is_pain002(content),
+        Parse,
+        "unsupported pain.002 version {}",
+        namespace.unwrap_or_default()
+    );
+    for token in Tokenizer::from(content) {
+        match token {
+            Ok(Token::ElementStart { local, .. }) => tag_stack.push(local.to_string()),
+            Ok(Token::ElementEnd { end, .. }) => match end {
+                ElementEnd::Empty => {
+                    tag_stack.pop();
+                }
+                ElementEnd::Close(_, local) => {
+                    tag_stack.pop();
+                    if local == "OrgnlPmtInfAndSts" {
+                        current.level = LEVEL_PAYMENT.to_string();
+                        current.msg_id = msg_id.clone();
+                        current.orgnl_msg_id = orgnl_msg_id.clone();
+                        // no status of the block: all transactions or the group have one
+                        if current.sts.is_empty() {
+                            current.sts = match tx_sts.first() {
+                                Some(sts) if tx_sts.iter().all(|other| other == sts) => sts.clone(),
+                                _ => grp_sts.clone(),
+                            };
+                        }
+                        ensure!(
+                            !current.sts.is_empty(),
+                            Parse,
+                            "no status for payment {} in pain.002",
+                            current.pmt_inf_id
+                        );
+                        statuses.push(std::mem::take(&mut current));
+                        tx_sts.clear();
+                    }
+                }
+                ElementEnd::Open => {}
+            },
+            Ok(Token::Attribute { local, value, .. }) => {
+                if local == "Ccy" && path_ends_with(&tag_stack, &["Amt", "InstdAmt"]) {
+                    instd_ccy = value.to_string();
+                }
+            }
+            Ok(Token::Text { text }) => {
+                let text = text.as_str();
+                if path_ends_with(&tag_stack, &["CstmrPmtStsRpt", "GrpHdr", "MsgId"]) {
+                    msg_id = text.to_string();
+                } else if path_ends_with(&tag_stack, &["OrgnlGrpInfAndSts", "OrgnlMsgId"]) {
+                    orgnl_msg_id = text.to_string();
+                } else if path_ends_with(&tag_stack, &["OrgnlGrpInfAndSts", "GrpSts"]) {
+                    grp_sts = text.to_string();
+                } else if path_ends_with(&tag_stack, &["OrgnlGrpInfAndSts", "OrgnlNbOfTxs"]) {
+                    grp_nb_of_txs = text.to_string();
+                } else if path_ends_with(&tag_stack, &["OrgnlGrpInfAndSts", "OrgnlCtrlSum"]) {
+                    grp_ctrl_sum = text.to_string();
+                } else if path_ends_with(&tag_stack, &["OrgnlPmtInfAndSts", "OrgnlPmtInfId"]) {
+                    current.pmt_inf_id = text.to_string();
+                } else if path_ends_with(&tag_stack, &["OrgnlPmtInfAndSts", "OrgnlNbOfTxs"]) {
+                    current.orgnl_nb_of_txs = text.to_string();
+                } else if path_ends_with(&tag_stack, &["OrgnlPmtInfAndSts", "OrgnlCtrlSum"]) {
+                    current.ctrl_sum = text.to_string();
+                } else if path_ends_with(&tag_stack, &["OrgnlPmtInfAndSts", "PmtInfSts"]) {
+                    current.sts = text.to_string();
+                } else if path_ends_with(&tag_stack, &["TxInfAndSts", "TxSts"]) {
+                    current.listed_txs += 1;
+                    tx_sts.push(text.to_string());
+                } else if path_ends_with(&tag_stack, &["OrgnlTxRef", "Amt", "InstdAmt"]) {
+                    if !current.ccy.is_empty() {
+                        ensure!(
+                            current.ccy == instd_ccy,
+                            Parse,
+                            "payment {} has amounts in several currencies",
+                            current.pmt_inf_id
+                        );
+                    }
+                    current.ccy = instd_ccy.clone();
+                    current.listed_amt_minor += to_minor_units(text, &instd_ccy, "CRDT");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => fail(HyperfridgeError::Parse(format!(
+                "error parsing pain.002 {}",
+                e
+            ))),
+        }
+    }
+    // HAC and PSR often only report the status of the whole group
+    if statuses.is_empty() {
+        ensure!(
+            !grp_sts.is_empty(),
+            Parse,
+            "no status for pain.001 {} in pain.002",
+            orgnl_msg_id
+        );
+        statuses.push(PaymentStatus {
+            level: LEVEL_GROUP.to_string(),
+            msg_id,
+            orgnl_msg_id,
+            orgnl_nb_of_txs: grp_nb_of_txs,
+            ctrl_sum: grp_ctrl_sum,
+            sts: grp_sts,
+            ..Default::default()
+        });
+    }
+    statuses
+}
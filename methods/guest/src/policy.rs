@@ -0,0 +1,269 @@
+//! Selective disclosure policy, given by the host as public input. It decides per field of the
+//! commitment whether the value is committed in cleartext, as salted hash or omitted:
+//!
+//! ```text
+//! # balances of the account, but not who owns it
+//! salt=4f1c0e6b2a93d8e5b1f07c2a6d9e3b48
+//! stmts.iban=hash
+//! stmts.othr_id=hash
+//! accounts=hash
+//! bind_to=hash
+//! payments.msg_id=hash
+//! payments.orgnl_msg_id=hash
+//! payments.pmt_inf_id=hash
+//! reserves.pub_bank_fingerprint=hash
+//! pub_client_pem=fingerprint
+//! stmts.ntries=omit
+//! ```
+//!
+//! Fields which are not listed are committed in cleartext. Hashed values are
+//! `hex(sha256(salt || value))`, the salt is shared off-chain with the verifier. Public keys
+//! can be committed as fingerprint `hex(sha256(DER))` instead of the PEM. The policy is not
+//! committed itself, only its hash, so the salt stays private. A policy which hides
+//! `stmts.iban` has to hide all other fields carrying the account or linking to its owner.
+
+use crate::{escape_json, fail, HyperfridgeError};
+use base64::{engine::general_purpose, Engine as _};
+use risc0_zkvm::sha::{Impl, Sha256};
+
+/// What a field of the commitment is, decides which disclosures are possible
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Value, // numbers, flags, objects: clear or omit
+    Text,  // strings and lists of strings: clear, hash or omit
+    Key,   // public keys as PEM: clear, hash, fingerprint or omit
+}
+
+/// Fields of the commitment which can be disclosed by policy
+const FIELDS: &[(&str, Kind)] = &[
+    ("challenge", Kind::Text),
+    ("bind_to", Kind::Text),
+    ("bind_checked", Kind::Value),
+    ("include_pending", Kind::Value),
+    ("tx_codes", Kind::Text),
+    ("order_type", Kind::Text),
+    ("bank_timestamp", Kind::Text),
+    ("accounts", Kind::Text),
+    ("pub_bank_pem", Kind::Key),
+    ("pub_witness_pems", Kind::Key),
+    ("witness_schemes", Kind::Value),
+    ("witness_keys_hash", Kind::Value),
+    ("witness_threshold", Kind::Value),
+    ("pub_client_pem", Kind::Key),
+    ("stmts", Kind::Value),
+    ("payments", Kind::Value),
+    ("reserves", Kind::Value),
+    ("fx", Kind::Value),
+    ("stmts.msg_version", Kind::Text),
+    ("stmts.iban", Kind::Text),
+    ("stmts.othr_id", Kind::Text),
+    ("stmts.othr_schme_nm", Kind::Text),
+    ("stmts.acct_ccy", Kind::Text),
+    ("stmts.elctrnc_seq_nb", Kind::Text),
+    ("stmts.fr_dt_tm", Kind::Text),
+    ("stmts.to_dt_tm", Kind::Text),
+    ("stmts.balances", Kind::Value),
+    ("stmts.ntries", Kind::Value),
+    ("stmts.txs_summry", Kind::Value),
+    ("payments.level", Kind::Text),
+    ("payments.msg_id", Kind::Text),
+    ("payments.orgnl_msg_id", Kind::Text),
+    ("payments.pmt_inf_id", Kind::Text),
+    ("payments.orgnl_nb_of_txs", Kind::Text),
+    ("payments.ctrl_sum", Kind::Text),
+    ("payments.listed_txs", Kind::Value),
+    ("payments.listed_amt_minor", Kind::Value),
+    ("payments.ccy", Kind::Text),
+    ("payments.sts", Kind::Text),
+    ("reserves.pub_bank_fingerprint", Kind::Text),
+];
+
+/// Fields which carry the account or link to its owner, hidden together with `stmts.iban`
+const ACCOUNT_FIELDS: &[&str] = &[
+    "accounts",
+    "stmts.iban",
+    "stmts.othr_id",
+    "bind_to",
+    "payments.msg_id",
+    "payments.orgnl_msg_id",
+    "payments.pmt_inf_id",
+    "reserves.pub_bank_fingerprint",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Disclosure {
+    Clear,
+    Hash,
+    Fingerprint,
+    Omit,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Policy {
+    salt: String,
+    fields: Vec<(String, Disclosure)>,
+    hash: String, // sha256 of the policy as given, empty without policy
+}
+
+impl Policy {
+    /// parses the policy, fails on unknown fields or if a field can not be hashed
+    pub(crate) fn parse(policy: &str) -> Policy {
+        let mut parsed = Policy::default();
+        if policy.trim().is_empty() {
+            return parsed;
+        }
+        parsed.hash = hex::encode(Impl::hash_bytes(policy.as_bytes()).as_bytes());
+        for line in policy.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (field, value) = line.split_once('=').unwrap_or_else(|| {
+                fail(HyperfridgeError::Parse(format!(
+                    "invalid policy line '{}'",
+                    line
+                )))
+            });
+            let (field, value) = (field.trim(), value.trim());
+            if field == "salt" {
+                parsed.salt = value.to_string();
+                continue;
+            }
+            let kind = FIELDS
+                .iter()
+                .find(|(name, _)| *name == field)
+                .unwrap_or_else(|| {
+                    fail(HyperfridgeError::Parse(format!(
+                        "unknown field '{}' in policy",
+                        field
+                    )))
+                })
+                .1;
+            let disclosure = match value {
+                "clear" => Disclosure::Clear,
+                "hash" if kind != Kind::Value => Disclosure::Hash,
+                "hash" => fail(HyperfridgeError::Parse(format!(
+                    "field '{}' can not be hashed",
+                    field
+                ))),
+                "fingerprint" if kind == Kind::Key => Disclosure::Fingerprint,
+                "fingerprint" => fail(HyperfridgeError::Parse(format!(
+                    "field '{}' is not a key",
+                    field
+                ))),
+                "omit" => Disclosure::Omit,
+                _ => fail(HyperfridgeError::Parse(format!(
+                    "invalid disclosure '{}' of field '{}' in policy",
+                    value, field
+                ))),
+            };
+            parsed.fields.push((field.to_string(), disclosure));
+        }
+        ensure!(
+            parsed.salt.len() >= 16 || !parsed.fields.iter().any(|(_, d)| *d == Disclosure::Hash),
+            Parse,
+            "policy with hashed fields needs a salt of at least 16 characters"
+        );
+        if parsed.disclosure("stmts.iban") != Disclosure::Clear {
+            if let Some(field) = ACCOUNT_FIELDS
+                .iter()
+                .find(|field| parsed.disclosure(field) == Disclosure::Clear)
+            {
+                fail(HyperfridgeError::Parse(format!(
+                    "policy hides stmts.iban but commits {} in cleartext",
+                    field
+                )));
+            }
+        }
+        parsed
+    }
+
+    /// hash of the policy for the commitment
+    pub(crate) fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    pub(crate) fn disclosure(&self, field: &str) -> Disclosure {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(name, _)| name == field)
+            .map(|(_, disclosure)| *disclosure)
+            .unwrap_or(Disclosure::Clear)
+    }
+
+    /// `"name":"value"` of a string field, `field` is the path as in the policy
+    pub(crate) fn string(&self, field: &str, value: &str) -> Option<String> {
+        let value = match self.disclosure(field) {
+            Disclosure::Clear => escape_json(value),
+            Disclosure::Hash => self.salted_hash(value),
+            Disclosure::Fingerprint => fingerprint(value),
+            Disclosure::Omit => return None,
+        };
+        Some(format!("\"{}\":\"{}\"", name(field), value))
+    }
+
+    /// `"name":["value",..]` of a list of strings, each value hashed on its own
+    pub(crate) fn strings(&self, field: &str, values: &[String]) -> Option<String> {
+        let values: Vec<String> = match self.disclosure(field) {
+            Disclosure::Clear => values.iter().map(|value| escape_json(value)).collect(),
+            Disclosure::Hash => values.iter().map(|value| self.salted_hash(value)).collect(),
+            Disclosure::Fingerprint => values.iter().map(|value| fingerprint(value)).collect(),
+            Disclosure::Omit => return None,
+        };
+        let values: Vec<String> = values
+            .iter()
+            .map(|value| format!("\"{}\"", value))
+            .collect();
+        Some(format!("\"{}\":[{}]", name(field), values.join(",")))
+    }
+
+    /// `"name":json` of a field which is committed as is or omitted
+    pub(crate) fn json(&self, field: &str, json: &str) -> Option<String> {
+        match self.disclosure(field) {
+            Disclosure::Omit => None,
+            _ => Some(format!("\"{}\":{}", name(field), json)),
+        }
+    }
+
+    /// hex(sha256(salt || value))
+    pub(crate) fn salted_hash(&self, value: &str) -> String {
+        let salted = format!("{}{}", self.salt, value);
+        hex::encode(Impl::hash_bytes(salted.as_bytes()).as_bytes())
+    }
+}
+
+/// hex(sha256(DER)) of a public key in PEM format, the same as
+/// `openssl pkey -pubin -outform DER | sha256sum`
+pub(crate) fn fingerprint(pem: &str) -> String {
+    let base64: String = pem
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = general_purpose::STANDARD
+        .decode(base64)
+        .unwrap_or_else(|e| {
+            fail(HyperfridgeError::Input(format!(
+                "error decoding PEM for fingerprint {}",
+                e
+            )))
+        });
+    hex::encode(Impl::hash_bytes(&der).as_bytes())
+}
+
+/// JSON object of the disclosed fields
+pub(crate) fn json_object(fields: Vec<Option<String>>) -> String {
+    format!(
+        "{{{}}}",
+        fields
+            .into_iter()
+            .flatten()
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
+
+/// name of the field in the commitment, `stmts.iban` is committed as `iban`
+fn name(field: &str) -> &str {
+    field.rsplit('.').next().unwrap_or(field)
+}
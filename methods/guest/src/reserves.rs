@@ -0,0 +1,130 @@
+//! Proof of reserves: the closing balances of all accounts in one or more EBICS responses,
+//! possibly of different banks, summed up per currency as of a date. Each response is
+//! verified on its own by `load()`, the totals can be compared with public liabilities
+//! given as `CCY=AMOUNT`, e.g. `CHF=1500000.00`.
+
+use crate::{closing_balances, currency_exponent, escape_json, to_minor_units};
+use crate::{fail, Document, HyperfridgeError};
+
+/// Total of the closing balances in one currency
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Total {
+    pub(crate) ccy: String,
+    pub(crate) total_minor: i64,
+    pub(crate) accounts: usize,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Reserves {
+    pub(crate) date: String,
+    pub(crate) oldest_dt: String, // date of the oldest closing balance used
+    pub(crate) totals: Vec<Total>,
+    pub(crate) liabilities: Vec<(String, i64)>, // currency and amount in minor units
+}
+
+impl Reserves {
+    /// sums up the latest closing balance up to `date` of each account per currency
+    pub(crate) fn of(documents: &[Document], date: &str, liabilities: &[String]) -> Reserves {
+        ensure!(
+            date.len() == 10 && date.chars().all(|c| c.is_ascii_digit() || c == '-'),
+            Input,
+            "invalid reserves date {}, expected YYYY-MM-DD",
+            date
+        );
+        let closing = closing_balances(documents, Some(date));
+        ensure!(
+            !closing.is_empty(),
+            Input,
+            "no closing balance up to {}",
+            date
+        );
+
+        let mut totals: Vec<Total> = Vec::new();
+        for balance in closing.iter() {
+            match totals.iter_mut().find(|total| total.ccy == balance.ccy) {
+                Some(total) => {
+                    total.total_minor += balance.amt_minor;
+                    total.accounts += 1;
+                }
+                None => totals.push(Total {
+                    ccy: balance.ccy.clone(),
+                    total_minor: balance.amt_minor,
+                    accounts: 1,
+                }),
+            }
+        }
+        totals.sort_by(|a, b| a.ccy.cmp(&b.ccy));
+
+        Reserves {
+            date: date.to_string(),
+            oldest_dt: closing
+                .iter()
+                .map(|balance| balance.dt.clone())
+                .min()
+                .unwrap_or_default(),
+            totals,
+            liabilities: liabilities
+                .iter()
+                .map(|liability| parse_liability(liability))
+                .collect(),
+        }
+    }
+
+    /// total in minor units of a currency, 0 if no account has this currency
+    fn total(&self, ccy: &str) -> i64 {
+        self.totals
+            .iter()
+            .find(|total| total.ccy == ccy)
+            .map(|total| total.total_minor)
+            .unwrap_or_default()
+    }
+
+    /// `banks` are the commitments of the banks of all responses
+    pub(crate) fn commitment(&self, banks: &[String]) -> String {
+        let totals = self
+            .totals
+            .iter()
+            .map(|total| {
+                format!(
+                    "{{\"ccy\":\"{}\",\"ccy_exp\":{},\"total_minor\":{},\"accounts\":{}}}",
+                    escape_json(&total.ccy),
+                    currency_exponent(&total.ccy),
+                    total.total_minor,
+                    total.accounts
+                )
+            })
+            .collect::<Vec<String>>();
+        let liabilities = self
+            .liabilities
+            .iter()
+            .map(|(ccy, amt_minor)| {
+                format!(
+                    "{{\"ccy\":\"{}\",\"amt_minor\":{},\"covered\":{}}}",
+                    escape_json(ccy),
+                    amt_minor,
+                    self.total(ccy) >= *amt_minor
+                )
+            })
+            .collect::<Vec<String>>();
+        format!(
+            "{{\"date\":\"{}\",\"oldest_dt\":\"{}\",\"banks\":[{}],\"totals\":[{}],\"liabilities\":[{}]}}",
+            escape_json(&self.date),
+            escape_json(&self.oldest_dt),
+            banks.join(","),
+            totals.join(","),
+            liabilities.join(",")
+        )
+    }
+}
+
+/// "CHF=1500000.00" to ("CHF", 150000000)
+fn parse_liability(liability: &str) -> (String, i64) {
+    let (ccy, amount) = liability.split_once('=').unwrap_or_else(|| {
+        fail(HyperfridgeError::Input(format!(
+            "invalid liability '{}', expected CCY=AMOUNT",
+            liability
+        )))
+    });
+    let (ccy, amount) = (ccy.trim(), amount.trim());
+    (ccy.to_string(), to_minor_units(amount, ccy, "CRDT"))
+}
@@ -17,9 +17,7 @@ fn test_signature_x() {
 const BANK_PUBLIC_KEY_X002_PEM: &str = include_str!("../../../data/pub_bank.pem");
 const USER_PRIVATE_KEY_E002_PEM: &str = include_str!("../../../data/client.pem");
 const WITNESS_PUBLIC_KEY: &str = include_str!("../../../data/pub_witness.pem");
-
-const TX_KEY_DECRYPTED: &[u8] =
-    include_bytes!("../../../data/test/test.xml-TransactionKeyDecrypt.bin");
+const WITNESS_PRIVATE_KEY: &str = include_str!("../../../data/witness.pem");
 
 macro_rules! include_resource {
     ($file:expr) => {
@@ -32,161 +30,38 @@ const SIGNATURE_VALUE_XML: &str = include_resource!("SignatureValue");
 const ORDER_DATA_XML: &str = include_resource!("OrderData");
 const WITNESS_SIGNATURE_HEX: &str = include_resource!("Witness.hex");
 
-#[test]
-fn test_print_imports() {
-    println!(" => {}", SIGNED_INFO_XML_C14N);
-    println!(" => {}", AUTHENTICATED_XML_C14N);
-    println!(" => {}", SIGNATURE_VALUE_XML);
-}
-
-#[test]
-fn test_digest() {
-    //A SHA-256 digest is a 256-bit string.
-    //The content of the DigestValue element shall be the base64
-    //encoding of this bit string viewed as a 32-octet octet stream.
-    let res = parse_ebics_response(
-        AUTHENTICATED_XML_C14N,
-        SIGNED_INFO_XML_C14N,
-        SIGNATURE_VALUE_XML,
-        ORDER_DATA_XML,
-    );
-    let authenticated = AUTHENTICATED_XML_C14N.as_bytes();
-    println!("  authenticated file length {:?}", &authenticated.len());
-
-    let sha = *Impl::hash_bytes(authenticated);
-    // println!("  digest object {:?}",&sha);
-    // println!("  digest calculated b64 {:?}",bytes_to_base64(sha.as_bytes()));
-    // println!("  digest should be in b64  {:?}",res.digest_value_b64);
-    // println!("  digest calculated hex {:?}",bytes_to_hex(sha.as_bytes()));
-    // println!("  digest should be in hex  {:?}",base64_to_hex(&res.digest_value_b64));
-
-    assert_eq!(
-        res.digest_value_b64,
-        general_purpose::STANDARD.encode(sha.as_bytes())
-    );
-}
-
-#[test]
-fn test_validate_signature() {
-    //-> Result<bool, Box<dyn Error>> {
-    //openssl pkeyutl  -verify -in "$signedinfo_digest_file" -sigfile "$signature_file"
-    //-pkeyopt rsa_padding_mode:pk1 -pkeyopt digest:sha256 -pubin -keyform PEM -inkey "$pem_file"
-
-    let pem = parse(BANK_PUBLIC_KEY_X002_PEM).expect("Failed to parse bank public key PEM");
-    let bank_public_key = RsaPublicKey::from_public_key_pem(&pem::encode(&pem))
-        .expect("Failed to create bank public key");
-    let request = parse_ebics_response(
-        AUTHENTICATED_XML_C14N,
-        SIGNED_INFO_XML_C14N,
-        SIGNATURE_VALUE_XML,
-        ORDER_DATA_XML,
-    );
-
-    verify_bank_signature(&bank_public_key, &request);
-}
-
-#[test]
-fn test_decrypt_txkey() {
-    // openssl pkeyutl -decrypt -in ${txkey_file} -out transaction_key.bin -inkey e002_private_key.pem -pkeyopt rsa_padding_mode:pkcs1
-    let request = parse_ebics_response(
-        AUTHENTICATED_XML_C14N,
-        SIGNED_INFO_XML_C14N,
-        SIGNATURE_VALUE_XML,
-        ORDER_DATA_XML,
-    );
-    // Parse the private key from PEM format
-    let private_key = RsaPrivateKey::from_pkcs8_pem(USER_PRIVATE_KEY_E002_PEM).unwrap();
-    let transaction_key_bin = decrypt_transaction_key(&request, &private_key, &Vec::new());
-    assert_eq!(transaction_key_bin.len(), 16);
-
+/// the test witness, who signed the test data
+fn test_witnesses() -> WitnessSet {
     let pub_witness = RsaPublicKey::from_public_key_pem(WITNESS_PUBLIC_KEY)
-        .expect("Failed to create pub_witness_key int est_decrypt_txkey");
+        .expect("Failed to create pub_witness_key for test");
     let witness_signature_bytes =
         Vec::from_hex(WITNESS_SIGNATURE_HEX.trim().replace([' ', '\n'], ""))
-            .expect("Failed to parse hexadecimal string witness_signature_hex test_decrypt_txkey");
-
-    let files = decrypt_order_data(
-        &request,
-        &transaction_key_bin,
-        &witness_signature_bytes,
-        &pub_witness,
-    );
-
-    for (index, item) in files.iter().enumerate() {
-        if index % 2 == 0 {
-            // Odd entries (by zero-based index): Filenames
-            let filename = String::from_utf8(item.clone())
-                .expect("Failed to convert filename bytes to string");
-
-            assert!(
-                filename.ends_with(".xml"),
-                "Filename does not end with .xml"
-            );
-        } else {
-            // Even entries: File contents
-            let content_start = String::from_utf8(item[0..5].to_vec())
-                .expect("Failed to convert content bytes to string");
-            //println!(" file {}",String::from_utf8(item.to_vec()).unwrap());
-            assert!(
-                content_start == "<?xml",
-                "File content does not start with <xml>"
-            );
-        }
+            .expect("Failed to parse hexadecimal string witness_signature_hex");
+    WitnessSet {
+        keys: vec![WitnessKey::Rsa(pub_witness)],
+        schemes: vec![WitnessScheme::RsaPkcs1],
+        signatures: vec![witness_signature_bytes],
+        threshold: 1,
     }
 }
 
-#[test]
-fn test_decrypt_txkey_reverse() {
-    //-> Result<bool, Box<dyn Error>> {
-    // openssl pkeyutl -decrypt -in ${txkey_file} -out transaction_key.bin -inkey e002_private_key.pem -pkeyopt rsa_padding_mode:pkcs1
+/// hash of the order data of the test file, which is signed by the witness
+fn test_order_data_hash() -> Vec<u8> {
     let request = parse_ebics_response(
         AUTHENTICATED_XML_C14N,
         SIGNED_INFO_XML_C14N,
         SIGNATURE_VALUE_XML,
         ORDER_DATA_XML,
-    );
-    // Parse the private key from PEM format
-    let private_key = RsaPrivateKey::from_pkcs8_pem(USER_PRIVATE_KEY_E002_PEM).unwrap();
-
-    let transaction_key_bin =
-        decrypt_transaction_key(&request, &private_key, &TX_KEY_DECRYPTED.to_vec());
-
-    assert_eq!(transaction_key_bin.len(), 16);
-
-    let pub_witness = RsaPublicKey::from_public_key_pem(WITNESS_PUBLIC_KEY)
-        .expect("Failed to create pub_witness_key test_decrypt_txkey_reverse");
-    let witness_signature_bytes =
-        Vec::from_hex(WITNESS_SIGNATURE_HEX.trim().replace([' ', '\n'], ""))
-            .expect("Failed to parse hexadecimal string witness_signature_hex");
-
-    let files = decrypt_order_data(
-        &request,
-        &transaction_key_bin,
-        &witness_signature_bytes,
-        &pub_witness,
-    );
+    )
+    .unwrap();
+    order_data_hash(&request).unwrap()
+}
 
-    for (index, item) in files.iter().enumerate() {
-        if index % 2 == 0 {
-            // Odd entries (by zero-based index): Filenames
-            let filename = String::from_utf8(item.clone())
-                .expect("Failed to convert filename bytes to string");
-
-            assert!(
-                filename.ends_with(".xml"),
-                "Filename does not end with .xml",
-            );
-        } else {
-            // Even entries: File contents
-            let content_start = String::from_utf8(item[0..5].to_vec())
-                .expect("Failed to convert content bytes to string");
-            //println!(" file {}",String::from_utf8(item.to_vec()).unwrap());
-            assert!(
-                content_start == "<?xml",
-                "File content does not start with <xml>"
-            );
-        }
-    }
+#[test]
+fn test_print_imports() {
+    println!(" => {}", SIGNED_INFO_XML_C14N);
+    println!(" => {}", AUTHENTICATED_XML_C14N);
+    println!(" => {}", SIGNATURE_VALUE_XML);
 }
 
 #[test]
@@ -198,31 +73,24 @@ fn test_parse() {
         SIGNED_INFO_XML_C14N,
         SIGNATURE_VALUE_XML,
         ORDER_DATA_XML,
-    );
+    )
+    .unwrap();
     let private_key = RsaPrivateKey::from_pkcs8_pem(USER_PRIVATE_KEY_E002_PEM).unwrap();
-    let pub_witness = RsaPublicKey::from_public_key_pem(WITNESS_PUBLIC_KEY)
-        .expect("Failed to create pub_witness_key test_parse");
-    let witness_signature_bytes =
-        Vec::from_hex(WITNESS_SIGNATURE_HEX.trim().replace([' ', '\n'], ""))
-            .expect("Failed to parse hexadecimal string witness_signature_hex");
 
-    let transaction_key_bin = decrypt_transaction_key(&request, &private_key, &Vec::new());
-    let files = decrypt_order_data(
-        &request,
-        &transaction_key_bin,
-        &witness_signature_bytes,
-        &pub_witness,
-    );
+    let transaction_key_bin = decrypt_transaction_key(&request, &private_key, &Vec::new()).unwrap();
+    let files = decrypt_order_data(&request, &transaction_key_bin).unwrap();
 
     for (index, item) in files.iter().enumerate() {
         if index == 1 {
             let camt =
-                parse_camt53(std::str::from_utf8(item).expect("Failed to convert to string"));
+                parse_camt53(std::str::from_utf8(item).expect("Failed to convert to string"))
+                    .unwrap();
 
             // <GrpHdr><MsgId>35e75effeaa74f579f97c8121bfa68ad</MsgId><CreDtTm>2023-11-29T22:54:31.6579278+01:00</CreDtTm>
             // <MsgPgntn><PgNb>1</PgNb><LastPgInd>true</LastPgInd></MsgPgntn></GrpHdr>
 
             assert_eq!(camt.grp_hdr.msg_id, "35e75effeaa74f579f97c8121bfa68ad");
+            assert_eq!(camt.msg_version, "camt.053.001.04");
             assert_eq!(camt.grp_hdr.cre_dt_tm, "2023-11-29T22:54:31.6579278+01:00");
             assert_eq!(camt.grp_hdr.pg_nb, 1);
             assert!(camt.grp_hdr.last_pg_ind);
@@ -247,6 +115,1094 @@ fn test_parse() {
             assert_eq!(camt.stmts[0].balances[1].amt, "31709.09");
             assert_eq!(camt.stmts[0].balances[1].cdt_dbt_ind, "CRDT");
             assert_eq!(camt.stmts[0].balances[1].dt, "2023-11-29");
+
+            // <Ntry><Amt Ccy="CHF">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts>
+            let ntry = &camt.stmts[0].ntries[0];
+            assert_eq!(camt.stmts[0].ntries.len(), 1);
+            assert_eq!(ntry.amt, "0.05");
+            assert_eq!(ntry.ccy, "CHF");
+            assert_eq!(ntry.cdtDbtInd, "DBIT");
+            assert_eq!(ntry.sts, "BOOK");
+
+            assert_eq!(ntry.btchNbOfTxs, Some(1));
+            check_batch(ntry);
+            let tx_dtls = &ntry.txDtls[0];
+            assert_eq!(tx_dtls.AmtValue, "0.05");
+            assert_eq!(tx_dtls.AmtCcy, "CHF");
+            assert_eq!(tx_dtls.CdtDbtInd, "DBIT");
+            assert_eq!(tx_dtls.DbtrNm, "element36 AG");
+            assert_eq!(tx_dtls.DbtrCtry.as_deref(), Some("CH"));
+            assert_eq!(tx_dtls.DbtrAcctIBAN, "CH4308307000289537312");
+            assert_eq!(tx_dtls.CdtrNm, "element36 AG");
+            assert_eq!(tx_dtls.CdtrCtry, None);
+            assert_eq!(tx_dtls.CdtrAcctIBAN, "CH2108307000289537320");
+            assert_eq!(tx_dtls.RmtInfUstrd.as_deref(), Some("test"));
+            assert_eq!(tx_dtls.AddtlTxInf.as_deref(), Some("test"));
         }
     }
 }
+
+/// all camt53 documents of the test response
+fn test_documents() -> Vec<Document> {
+    let request = parse_ebics_response(
+        AUTHENTICATED_XML_C14N,
+        SIGNED_INFO_XML_C14N,
+        SIGNATURE_VALUE_XML,
+        ORDER_DATA_XML,
+    )
+    .unwrap();
+    let private_key = RsaPrivateKey::from_pkcs8_pem(USER_PRIVATE_KEY_E002_PEM).unwrap();
+    let transaction_key_bin = decrypt_transaction_key(&request, &private_key, &Vec::new()).unwrap();
+    decrypt_order_data(&request, &transaction_key_bin)
+        .unwrap()
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .map(|item| parse_camt53(std::str::from_utf8(item).unwrap()).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_bind_to_found() {
+    // the test data has a debit with <AddtlTxInf>test</AddtlTxInf>
+    find_binding(&test_documents(), "test");
+}
+
+#[test]
+#[should_panic(expected = "[binding_not_proven] no outgoing transfer")]
+fn test_bind_to_not_found() {
+    find_binding(
+        &test_documents(),
+        "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+    );
+}
+
+#[test]
+#[should_panic(expected = "[binding_not_proven] no outgoing transfer")]
+fn test_bind_to_prefix() {
+    // the debit carries "test", a prefix of it is no binding
+    find_binding(&test_documents(), "tes");
+}
+
+#[test]
+fn test_carries_binding() {
+    assert!(carries_binding(" did:example:123 ", "did:example:123"));
+    assert!(carries_binding("hyperfridge ref:0x1234", "0x1234"));
+    assert!(!carries_binding("0x1234", "0x12"));
+    assert!(!carries_binding("did:example:123", "did:example"));
+    assert!(!carries_binding("did:example:1234", "did:example:123"));
+}
+
+#[test]
+#[should_panic(expected = "binding not proven")]
+fn test_bind_to_credit_ignored() {
+    // only the credit entry of the second account carries the binding
+    let documents: Vec<Document> = test_documents()
+        .into_iter()
+        .filter(|document| document.stmts[0].iban == "CH2108307000289537320")
+        .collect();
+    assert_eq!(documents.len(), 1);
+    find_binding(&documents, "test");
+}
+
+#[test]
+fn test_witness_threshold() {
+    // bank did not sign, but one of two is enough
+    let mut witnesses = test_witnesses();
+    let pem = parse(BANK_PUBLIC_KEY_X002_PEM).expect("Failed to parse bank public key PEM");
+    witnesses.keys.push(WitnessKey::Rsa(
+        RsaPublicKey::from_public_key_pem(&pem::encode(&pem)).unwrap(),
+    ));
+    witnesses.schemes.push(WitnessScheme::RsaPkcs1);
+    witnesses.signatures.push(Vec::new());
+    verify_witness_signatures(&witnesses, &test_order_data_hash());
+}
+
+#[test]
+#[should_panic(expected = "[witness_invalid] Order Data Signature could not be verified")]
+fn test_witness_threshold_not_reached() {
+    let mut witnesses = test_witnesses();
+    let pem = parse(BANK_PUBLIC_KEY_X002_PEM).expect("Failed to parse bank public key PEM");
+    witnesses.keys.push(WitnessKey::Rsa(
+        RsaPublicKey::from_public_key_pem(&pem::encode(&pem)).unwrap(),
+    ));
+    witnesses.schemes.push(WitnessScheme::RsaPkcs1);
+    // the witness signature is not valid for the bank key
+    witnesses.signatures.push(witnesses.signatures[0].clone());
+    witnesses.threshold = 2;
+    verify_witness_signatures(&witnesses, &test_order_data_hash());
+}
+
+#[test]
+#[should_panic(expected = "[input] witness key 1 is listed more than once")]
+fn test_witness_duplicate_key_rejected() {
+    let mut witnesses = test_witnesses();
+    witnesses.keys.push(witnesses.keys[0].clone());
+    witnesses.schemes.push(WitnessScheme::RsaPkcs1);
+    witnesses.signatures.push(witnesses.signatures[0].clone());
+    witnesses.threshold = 2;
+    verify_witness_signatures(&witnesses, &test_order_data_hash());
+}
+
+#[test]
+#[should_panic(expected = "[input] witness key 1 is listed more than once")]
+fn test_witness_duplicate_key_other_scheme() {
+    // the same key under another scheme would count twice in witness_keys_hash
+    let mut witnesses = test_witnesses();
+    witnesses.keys.push(witnesses.keys[0].clone());
+    witnesses.schemes.push(WitnessScheme::RsaPss);
+    witnesses.signatures.push(Vec::new());
+    verify_witness_signatures(&witnesses, &test_order_data_hash());
+}
+
+/// witness set with a single witness using the given scheme
+fn single_witness(key: WitnessKey, scheme: WitnessScheme, signature: Vec<u8>) -> WitnessSet {
+    WitnessSet {
+        keys: vec![key],
+        schemes: vec![scheme],
+        signatures: vec![signature],
+        threshold: 1,
+    }
+}
+
+#[test]
+fn test_witness_scheme_rsa_pss() {
+    let hash = test_order_data_hash();
+    let witness_key = RsaPrivateKey::from_pkcs8_pem(WITNESS_PRIVATE_KEY).unwrap();
+    let signature = witness_key
+        .sign_with_rng(&mut rand::thread_rng(), Pss::new::<RsaSha256>(), &hash)
+        .unwrap();
+    let key = WitnessKey::from_pem(WITNESS_PUBLIC_KEY, WitnessScheme::RsaPss);
+    verify_witness_signatures(
+        &single_witness(key.clone(), WitnessScheme::RsaPss, signature.clone()),
+        &hash,
+    );
+    // a PSS signature is no PKCS#1 v1.5 signature
+    assert!(key
+        .verify(WitnessScheme::RsaPkcs1, &hash, &signature)
+        .is_err());
+}
+
+#[test]
+fn test_witness_scheme_ecdsa_p256() {
+    use p256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+    let hash = test_order_data_hash();
+    let signing_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let signature: Signature = signing_key.sign_prehash(&hash).unwrap();
+    let pem =
+        EncodePublicKey::to_public_key_pem(signing_key.verifying_key(), LineEnding::LF).unwrap();
+    let key = WitnessKey::from_pem(&pem, WitnessScheme::EcdsaP256);
+
+    // DER and fixed size signatures are accepted
+    verify_witness_signatures(
+        &single_witness(
+            key.clone(),
+            WitnessScheme::EcdsaP256,
+            signature.to_der().as_bytes().to_vec(),
+        ),
+        &hash,
+    );
+    verify_witness_signatures(
+        &single_witness(key, WitnessScheme::EcdsaP256, signature.to_vec()),
+        &hash,
+    );
+}
+
+#[test]
+fn test_witness_scheme_ed25519() {
+    use ed25519_dalek::{Signer, SigningKey};
+    let hash = test_order_data_hash();
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    let signature = signing_key.sign(&hash).to_bytes().to_vec();
+    let pem =
+        EncodePublicKey::to_public_key_pem(&signing_key.verifying_key(), LineEnding::LF).unwrap();
+    let key = WitnessKey::from_pem(&pem, WitnessScheme::Ed25519);
+    verify_witness_signatures(
+        &single_witness(key.clone(), WitnessScheme::Ed25519, signature.clone()),
+        &hash,
+    );
+    // wrong hash
+    assert!(key
+        .verify(WitnessScheme::Ed25519, &[0u8; 32], &signature)
+        .is_err());
+}
+
+#[test]
+fn test_witness_scheme_names() {
+    assert_eq!(WitnessScheme::parse("A005"), WitnessScheme::RsaPkcs1);
+    assert_eq!(WitnessScheme::parse("A006"), WitnessScheme::RsaPss);
+    for scheme in [
+        WitnessScheme::RsaPkcs1,
+        WitnessScheme::RsaPss,
+        WitnessScheme::EcdsaP256,
+        WitnessScheme::Ed25519,
+    ] {
+        assert_eq!(WitnessScheme::parse(scheme.name()), scheme);
+    }
+}
+
+#[test]
+fn test_account_parse() {
+    let account = Account::parse("CH4308307000289537312:chf");
+    assert_eq!(account.id, "CH4308307000289537312");
+    assert_eq!(account.schme_nm, "");
+    assert_eq!(account.ccy, "CHF");
+    assert_eq!(account.name(), "CH4308307000289537312:CHF");
+    assert_eq!(Account::parse("CH4308307000289537312").ccy, "");
+}
+
+#[test]
+fn test_account_matches() {
+    let documents = test_documents();
+    let stmts: Vec<&Stmt> = documents.iter().flat_map(|d| d.stmts.iter()).collect();
+    let accounts = [
+        Account::parse("CH4308307000289537312"),
+        Account::parse("CH2108307000289537320"),
+    ];
+    // both accounts of the test response are found, the first one has the statements
+    // with ElctrncSeqNb 247 and 248
+    let counts: Vec<usize> = accounts
+        .iter()
+        .map(|account| stmts.iter().filter(|stmt| account.matches(stmt)).count())
+        .collect();
+    assert_eq!(counts, vec![2, 1]);
+    // and every statement belongs to one of them
+    assert!(stmts
+        .iter()
+        .all(|stmt| accounts.iter().any(|account| account.matches(stmt))));
+    // the test data has no <Ccy> in <Acct>, a currency filter excludes the statements
+    let eur = Account::parse("CH4308307000289537312:EUR");
+    assert!(!stmts.iter().any(|stmt| eur.matches(stmt)));
+}
+
+#[test]
+fn test_othr_account() {
+    let camt = parse_camt53(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><Stmt><ElctrncSeqNb>1</ElctrncSeqNb>",
+        "<Acct><Id><Othr><Id>123456789</Id><SchmeNm><Cd>BBAN</Cd></SchmeNm></Othr></Id><Ccy>USD</Ccy></Acct>",
+        "<Bal><Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp><Amt Ccy=\"USD\">10.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Bal>",
+        "</Stmt></BkToCstmrStmt></Document>"
+    )).unwrap();
+    let stmt = &camt.stmts[0];
+    assert_eq!(stmt.iban, "");
+    assert_eq!(stmt.othr_id, "123456789");
+    assert_eq!(stmt.othr_schme_nm, "BBAN");
+    assert_eq!(stmt.ccy, "USD");
+
+    assert!(Account::parse("123456789").matches(stmt));
+    assert!(Account::parse("BBAN/123456789:USD").matches(stmt));
+    assert!(!Account::parse("UPIC/123456789").matches(stmt));
+    assert_eq!(
+        Account::parse("BBAN/123456789:usd").name(),
+        "BBAN/123456789:USD"
+    );
+}
+
+#[test]
+fn test_balance_commitment() {
+    let camt = parse_camt53(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><Stmt><Acct><Id><IBAN>CH4308307000289537312</IBAN></Id></Acct>",
+        "<Bal><Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp><Amt Ccy=\"CHF\">100.00</Amt><CdtDbtInd>DBIT</CdtDbtInd><Dt><Dt>2023-11-29</Dt></Dt></Bal>",
+        "<Bal><Tp><CdOrPrtry><Prtry>XPND</Prtry></CdOrPrtry></Tp><Amt Ccy=\"CHF\">5.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Dt><DtTm>2023-11-29T12:00:00</DtTm></Dt></Bal>",
+        "</Stmt></BkToCstmrStmt></Document>"
+    )).unwrap();
+    let balances = &camt.stmts[0].balances;
+    assert_eq!(
+        balance_commitment(&balances[0]),
+        r#"{"cd":"CLBD","amt":"100.00","amt_minor":-10000,"ccy":"CHF","ccy_exp":2,"cdt_dbt_ind":"DBIT","dt":"2023-11-29"}"#
+    );
+    assert_eq!(balances[1].cd, "XPND");
+    assert_eq!(balances[1].dt, "2023-11-29T12:00:00");
+}
+
+#[test]
+fn test_minor_units() {
+    assert_eq!(to_minor_units("31709.14", "CHF", "CRDT"), 3170914);
+    assert_eq!(to_minor_units("3.7", "CHF", "CRDT"), 370);
+    assert_eq!(to_minor_units("100", "EUR", "DBIT"), -10000);
+    assert_eq!(to_minor_units("0.05", "CHF", "DBIT"), -5);
+    assert_eq!(to_minor_units("1500", "JPY", "CRDT"), 1500);
+    assert_eq!(to_minor_units("1.234", "KWD", "CRDT"), 1234);
+}
+
+#[test]
+#[should_panic(expected = "more fraction digits than JPY allows")]
+fn test_minor_units_fraction_digits() {
+    to_minor_units("1.5", "JPY", "CRDT");
+}
+
+#[test]
+#[should_panic(expected = "invalid amount")]
+fn test_minor_units_invalid_amount() {
+    to_minor_units("1,00", "CHF", "CRDT");
+}
+
+#[test]
+#[should_panic(expected = "invalid credit debit indicator")]
+fn test_minor_units_missing_sign() {
+    to_minor_units("1.00", "CHF", "");
+}
+
+/// camt53 page with one statement and one balance
+fn camt53_page(msg_id: &str, pg_nb: u8, last_pg_ind: bool, bal: &str, amt: &str) -> String {
+    format!(
+        concat!(
+            "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><GrpHdr><MsgId>{}</MsgId>",
+            "<MsgPgntn><PgNb>{}</PgNb><LastPgInd>{}</LastPgInd></MsgPgntn></GrpHdr>",
+            "<Stmt><Id>stmt-{}</Id><ElctrncSeqNb>7</ElctrncSeqNb>",
+            "<Acct><Id><IBAN>CH4308307000289537312</IBAN></Id></Acct>",
+            "<Bal><Tp><CdOrPrtry><Cd>{}</Cd></CdOrPrtry></Tp><Amt Ccy=\"CHF\">{}</Amt><CdtDbtInd>CRDT</CdtDbtInd></Bal>",
+            "</Stmt></BkToCstmrStmt></Document>"
+        ),
+        msg_id, pg_nb, last_pg_ind, msg_id, bal, amt
+    )
+}
+
+#[test]
+fn test_merge_pages() {
+    let pages = vec![
+        parse_camt53(&camt53_page("msg", 2, true, "CLBD", "2.00")).unwrap(),
+        parse_camt53(&camt53_page("other", 1, true, "OPBD", "9.00")).unwrap(),
+        parse_camt53(&camt53_page("msg", 1, false, "OPBD", "1.00")).unwrap(),
+    ];
+    let documents = merge_pages(pages);
+    assert_eq!(documents.len(), 2);
+    assert_eq!(documents[0].grp_hdr.msg_id, "msg");
+    assert_eq!(documents[0].stmts.len(), 1);
+    let balances = &documents[0].stmts[0].balances;
+    assert_eq!(balances[0].cd, "OPBD");
+    assert_eq!(balances[1].cd, "CLBD");
+    assert_eq!(documents[1].grp_hdr.msg_id, "other");
+}
+
+#[test]
+#[should_panic(expected = "page 2 of camt53 message msg is missing")]
+fn test_merge_pages_missing_page() {
+    merge_pages(vec![
+        parse_camt53(&camt53_page("msg", 1, false, "OPBD", "1.00")).unwrap(),
+        parse_camt53(&camt53_page("msg", 3, true, "CLBD", "2.00")).unwrap(),
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "camt53 message msg is incomplete")]
+fn test_merge_pages_last_page_missing() {
+    merge_pages(vec![
+        parse_camt53(&camt53_page("msg", 1, false, "OPBD", "1.00")).unwrap(),
+        parse_camt53(&camt53_page("msg", 2, false, "CLBD", "2.00")).unwrap(),
+    ]);
+}
+
+#[test]
+fn test_camt53_v02() {
+    let camt = parse_camt53(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.02\"><BkToCstmrStmt><Stmt>",
+        "<Ntry><Amt Ccy=\"EUR\">12.50</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts><NtryDtls><TxDtls>",
+        "<AmtDtls><TxAmt><Amt Ccy=\"EUR\">12.50</Amt></TxAmt></AmtDtls>",
+        "<RltdPties><Cdtr><Nm>Muster GmbH</Nm></Cdtr></RltdPties>",
+        "</TxDtls></NtryDtls></Ntry></Stmt></BkToCstmrStmt></Document>"
+    )).unwrap();
+    assert_eq!(camt.msg_version, "camt.053.001.02");
+    let ntry = &camt.stmts[0].ntries[0];
+    assert_eq!(ntry.sts, "BOOK");
+    assert_eq!(ntry.txDtls[0].AmtValue, "12.50");
+    assert_eq!(ntry.txDtls[0].AmtCcy, "EUR");
+    // inherited from the entry
+    assert_eq!(ntry.txDtls[0].CdtDbtInd, "DBIT");
+    assert_eq!(ntry.txDtls[0].CdtrNm, "Muster GmbH");
+}
+
+#[test]
+fn test_camt53_v08() {
+    let camt = parse_camt53(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.08\"><BkToCstmrStmt><Stmt>",
+        "<Ntry><Amt Ccy=\"CHF\">1.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts><Cd>BOOK</Cd></Sts><NtryDtls><TxDtls>",
+        "<Amt Ccy=\"CHF\">1.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>",
+        "<RltdPties><Dbtr><Pty><Nm>element36 AG</Nm><PstlAdr><Ctry>CH</Ctry></PstlAdr></Pty></Dbtr></RltdPties>",
+        "</TxDtls></NtryDtls></Ntry></Stmt></BkToCstmrStmt></Document>"
+    )).unwrap();
+    assert_eq!(camt.msg_version, "camt.053.001.08");
+    let ntry = &camt.stmts[0].ntries[0];
+    assert_eq!(ntry.sts, "BOOK");
+    assert_eq!(ntry.txDtls[0].AmtValue, "1.00");
+    assert_eq!(ntry.txDtls[0].DbtrNm, "element36 AG");
+    assert_eq!(ntry.txDtls[0].DbtrCtry.as_deref(), Some("CH"));
+}
+
+const MT940_SAMPLE: &str =
+    "{1:F01ELEMCHZZAXXX0000000000}{2:O9401200231129ELEMCHZZAXXX00000000002311291200N}{4:
+:20:STMT20231129
+:25:CH4308307000289537312
+:28C:00247/001
+:60F:C231129CHF31709,14
+:61:2311291129D0,05NTRFNONREF//7463d4a6
+:86:5GrwvaEF5zXb26Fz9rcQpDWS57CtERHp
+NehXCPcNoHGKutQY
+:62F:C231129CHF31709,09
+:64:C231129CHF31709,09
+-}";
+
+#[test]
+fn test_mt940() {
+    assert!(mt940::is_mt940(MT940_SAMPLE));
+    assert!(!mt940::is_mt940("<?xml version=\"1.0\"?><Document/>"));
+
+    let documents = mt940::parse_mt940(MT940_SAMPLE);
+    assert_eq!(documents.len(), 1);
+    let document = &documents[0];
+    assert_eq!(document.msg_version, "MT940");
+    assert_eq!(document.grp_hdr.msg_id, "STMT20231129");
+
+    let stmt = &document.stmts[0];
+    assert_eq!(stmt.iban, "CH4308307000289537312");
+    assert_eq!(stmt.elctrnc_seq_nb, "247");
+    assert_eq!(stmt.fr_dt_tm, "2023-11-29T00:00:00");
+    assert_eq!(stmt.balances.len(), 3);
+    assert_eq!(stmt.balances[0].cd, "OPBD");
+    assert_eq!(stmt.balances[0].amt, "31709.14");
+    assert_eq!(stmt.balances[1].cd, "CLBD");
+    assert_eq!(stmt.balances[2].cd, "CLAV");
+    let closing = &stmt.balances[1];
+    assert_eq!(
+        to_minor_units(&closing.amt, &closing.ccy, &closing.cdt_dbt_ind),
+        3170909
+    );
+
+    let ntry = &stmt.ntries[0];
+    assert_eq!(ntry.amt, "0.05");
+    assert_eq!(ntry.ccy, "CHF");
+    assert_eq!(ntry.cdtDbtInd, "DBIT");
+    assert_eq!(ntry.bkTxCd.prtry, "NTRF");
+    // :86: continuation lines are joined, the binding check works as for camt53
+    find_binding(
+        &documents,
+        "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+    );
+}
+
+#[test]
+fn test_mt940_bank_code_account() {
+    // German STA statement with bank code (BLZ) and account number in :25:
+    let documents = mt940::parse_mt940(concat!(
+        ":20:STARTUMS\n",
+        ":25:10020030/1234567\n",
+        ":28C:00012/001\n",
+        ":60F:C231128EUR100,00\n",
+        ":62F:C231129EUR100,00\n",
+        "-\n"
+    ));
+    let stmt = &documents[0].stmts[0];
+    assert_eq!(stmt.othr_id, "10020030/1234567");
+    assert!(Account::parse("10020030/1234567").matches(stmt));
+    assert!(!Account::parse("10020030/7654321").matches(stmt));
+    assert_eq!(
+        Account::parse("10020030/1234567").name(),
+        "10020030/1234567"
+    );
+}
+
+#[test]
+fn test_mt942() {
+    let documents = mt940::parse_mt940(concat!(
+        ":20:INTRADAY1\n",
+        ":25:BBAN123456789\n",
+        ":28C:12/1\n",
+        ":34F:USD0,\n",
+        ":13D:2311291530+0100\n",
+        ":61:231129C1000,NTRFNONREF\n",
+        "-\n"
+    ));
+    let document = &documents[0];
+    assert_eq!(document.msg_version, "MT942");
+    assert_eq!(document.stmts[0].othr_id, "BBAN123456789");
+    assert_eq!(document.stmts[0].to_dt_tm, "2023-11-29T00:00:00");
+    assert_eq!(document.stmts[0].ntries[0].amt, "1000");
+    assert_eq!(document.stmts[0].ntries[0].ccy, "USD");
+    assert_eq!(document.stmts[0].ntries[0].cdtDbtInd, "CRDT");
+}
+
+const PAIN002_SAMPLE: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+    "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:pain.002.001.03\"><CstmrPmtStsRpt>",
+    "<GrpHdr><MsgId>STS-1</MsgId><CreDtTm>2023-11-29T10:00:00</CreDtTm></GrpHdr>",
+    "<OrgnlGrpInfAndSts><OrgnlMsgId>PAIN001-1</OrgnlMsgId><OrgnlMsgNmId>pain.001.001.03</OrgnlMsgNmId><GrpSts>PART</GrpSts></OrgnlGrpInfAndSts>",
+    "<OrgnlPmtInfAndSts><OrgnlPmtInfId>PMT-1</OrgnlPmtInfId><OrgnlNbOfTxs>2</OrgnlNbOfTxs><OrgnlCtrlSum>150.50</OrgnlCtrlSum><PmtInfSts>ACSC</PmtInfSts>",
+    "<TxInfAndSts><TxSts>ACSC</TxSts><OrgnlTxRef><Amt><InstdAmt Ccy=\"CHF\">100.00</InstdAmt></Amt></OrgnlTxRef></TxInfAndSts>",
+    "<TxInfAndSts><TxSts>ACSC</TxSts><OrgnlTxRef><Amt><InstdAmt Ccy=\"CHF\">50.50</InstdAmt></Amt></OrgnlTxRef></TxInfAndSts>",
+    "</OrgnlPmtInfAndSts>",
+    "<OrgnlPmtInfAndSts><OrgnlPmtInfId>PMT-2</OrgnlPmtInfId>",
+    "<TxInfAndSts><TxSts>RJCT</TxSts><OrgnlTxRef><Amt><InstdAmt Ccy=\"EUR\">10</InstdAmt></Amt></OrgnlTxRef></TxInfAndSts>",
+    "</OrgnlPmtInfAndSts></CstmrPmtStsRpt></Document>"
+);
+
+#[test]
+fn test_pain002() {
+    assert!(pain002::is_pain002(PAIN002_SAMPLE));
+    let payments = pain002::parse_pain002(PAIN002_SAMPLE);
+    assert_eq!(payments.len(), 2);
+    assert_eq!(payments[0].msg_id, "STS-1");
+    assert_eq!(payments[0].orgnl_msg_id, "PAIN001-1");
+    assert_eq!(payments[0].level, pain002::LEVEL_PAYMENT);
+    assert_eq!(payments[0].pmt_inf_id, "PMT-1");
+    assert_eq!(payments[0].orgnl_nb_of_txs, "2");
+    assert_eq!(payments[0].listed_txs, 2);
+    assert_eq!(payments[0].ctrl_sum, "150.50");
+    assert_eq!(payments[0].listed_amt_minor, 15050);
+    assert_eq!(payments[0].ccy, "CHF");
+    assert_eq!(payments[0].sts, "ACSC");
+    // no PmtInfSts, status of the transactions
+    assert_eq!(payments[1].sts, "RJCT");
+    assert_eq!(payments[1].listed_amt_minor, 1000);
+    assert_eq!(
+        payment_commitment(&payments[1], &Policy::default()),
+        r#"{"level":"payment","msg_id":"STS-1","orgnl_msg_id":"PAIN001-1","pmt_inf_id":"PMT-2","orgnl_nb_of_txs":"","ctrl_sum":"","listed_txs":1,"listed_amt_minor":1000,"ccy":"EUR","sts":"RJCT"}"#
+    );
+}
+
+#[test]
+fn test_pain002_group_status() {
+    // HAC with the status of the whole pain.001 only
+    let payments = pain002::parse_pain002(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:pain.002.001.03\"><CstmrPmtStsRpt>",
+        "<GrpHdr><MsgId>STS-2</MsgId></GrpHdr>",
+        "<OrgnlGrpInfAndSts><OrgnlMsgId>PAIN001-2</OrgnlMsgId><OrgnlMsgNmId>pain.001.001.03</OrgnlMsgNmId>",
+        "<OrgnlNbOfTxs>3</OrgnlNbOfTxs><OrgnlCtrlSum>300.00</OrgnlCtrlSum><GrpSts>ACCP</GrpSts></OrgnlGrpInfAndSts>",
+        "</CstmrPmtStsRpt></Document>"
+    ));
+    assert_eq!(payments.len(), 1);
+    assert_eq!(
+        payment_commitment(&payments[0], &Policy::default()),
+        r#"{"level":"group","msg_id":"STS-2","orgnl_msg_id":"PAIN001-2","pmt_inf_id":"","orgnl_nb_of_txs":"3","ctrl_sum":"300.00","listed_txs":0,"listed_amt_minor":0,"ccy":"","sts":"ACCP"}"#
+    );
+}
+
+#[test]
+#[should_panic(expected = "[parse] no status for pain.001 PAIN001-3")]
+fn test_pain002_without_status() {
+    pain002::parse_pain002(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:pain.002.001.10\"><CstmrPmtStsRpt>",
+        "<OrgnlGrpInfAndSts><OrgnlMsgId>PAIN001-3</OrgnlMsgId></OrgnlGrpInfAndSts>",
+        "</CstmrPmtStsRpt></Document>"
+    ));
+}
+
+#[test]
+#[should_panic(expected = "unsupported pain.002 version")]
+fn test_pain002_unknown_version() {
+    pain002::parse_pain002(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:pain.002.001.02\"></Document>",
+    );
+}
+
+#[test]
+fn test_pain002_namespace() {
+    assert!(pain002::is_pain002(
+        "<p:Document xmlns:p=\"urn:iso:std:iso:20022:tech:xsd:pain.002.001.10\"></p:Document>"
+    ));
+    // the namespace has to be the one of the document, not just somewhere in the order data
+    assert!(!pain002::is_pain002(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt>",
+        "<AddtlInf>urn:iso:std:iso:20022:tech:xsd:pain.002.001.03</AddtlInf></BkToCstmrStmt></Document>"
+    )));
+    assert!(!pain002::is_pain002(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:pain.002.001.02\"></Document>"
+    ));
+}
+
+#[test]
+#[should_panic(expected = "[parse] error parsing pain.002")]
+fn test_pain002_invalid_xml() {
+    pain002::parse_pain002(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:pain.002.001.03\"><GrpHdr =></Document>",
+    );
+}
+
+#[test]
+fn test_entry_summary() {
+    let camt = parse_camt53(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><Stmt>",
+        "<Ntry><Amt Ccy=\"CHF\">100.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts>BOOK</Sts></Ntry>",
+        "<Ntry><Amt Ccy=\"CHF\">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts></Ntry>",
+        "<Ntry><Amt Ccy=\"CHF\">50.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts>PDNG</Sts></Ntry>",
+        "<Ntry><Amt Ccy=\"CHF\">20.00</Amt><CdtDbtInd>DBIT</CdtDbtInd><RvslInd>true</RvslInd><Sts>BOOK</Sts></Ntry>",
+        "<Ntry><Amt Ccy=\"CHF\">5.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts>INFO</Sts></Ntry>",
+        "</Stmt></BkToCstmrStmt></Document>"
+    )).unwrap();
+    let summary = EntrySummary::of(&camt.stmts[0], &TxCodeFilter::default());
+    // only booked entries which are not reversed count
+    assert_eq!(
+        summary,
+        EntrySummary {
+            booked: 2,
+            pending: 1,
+            info: 1,
+            reversed: 1,
+            txs: 2,
+            ccy: "CHF".to_string(),
+            inflow_minor: 10000,
+            outflow_minor: 5,
+        }
+    );
+    assert_eq!(
+        summary.commitment(),
+        r#"{"booked":2,"pending":1,"info":1,"reversed":1,"txs":2,"ccy":"CHF","inflow_minor":10000,"outflow_minor":5}"#
+    );
+}
+
+#[test]
+#[should_panic(expected = "binding not proven")]
+fn test_bind_to_pending_ignored() {
+    let camt = parse_camt53(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><Stmt>",
+        "<Ntry><Amt Ccy=\"CHF\">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>PDNG</Sts><NtryDtls><TxDtls>",
+        "<Amt Ccy=\"CHF\">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd><AddtlTxInf>did:example:123</AddtlTxInf>",
+        "</TxDtls></NtryDtls></Ntry></Stmt></BkToCstmrStmt></Document>"
+    )).unwrap();
+    find_binding(&[camt], "did:example:123");
+}
+
+#[test]
+fn test_bind_to_entry_without_tx_dtls() {
+    let camt = parse_camt53(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><Stmt>",
+        "<Ntry><Amt Ccy=\"CHF\">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts>",
+        "<AddtlNtryInf>did:example:123</AddtlNtryInf></Ntry></Stmt></BkToCstmrStmt></Document>"
+    ))
+    .unwrap();
+    find_binding(&[camt], "did:example:123");
+}
+
+/// batch entry with the given entry amount and transaction details
+fn camt53_batch(amt: &str, nb_of_txs: usize, tx_amts: &[&str]) -> Document {
+    let tx_dtls: String = tx_amts
+        .iter()
+        .map(|tx_amt| {
+            format!(
+                "<TxDtls><AmtDtls><TxAmt><Amt Ccy=\"CHF\">{}</Amt></TxAmt></AmtDtls><CdtDbtInd>DBIT</CdtDbtInd></TxDtls>",
+                tx_amt
+            )
+        })
+        .collect();
+    parse_camt53(&format!(
+        concat!(
+            "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><Stmt>",
+            "<Ntry><Amt Ccy=\"CHF\">{}</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts>",
+            "<NtryDtls><Btch><NbOfTxs>{}</NbOfTxs></Btch>{}</NtryDtls></Ntry>",
+            "</Stmt></BkToCstmrStmt></Document>"
+        ),
+        amt, nb_of_txs, tx_dtls
+    )).unwrap()
+}
+
+#[test]
+fn test_batch() {
+    let camt = camt53_batch("3500.00", 3, &["1000.00", "1500.00", "1000"]);
+    let ntry = &camt.stmts[0].ntries[0];
+    check_batch(ntry);
+    assert_eq!(ntry.transactions().len(), 3);
+    assert_eq!(ntry.transactions()[1].AmtValue, "1500.00");
+    let summary = EntrySummary::of(&camt.stmts[0], &TxCodeFilter::default());
+    assert_eq!(summary.txs, 3);
+    assert_eq!(summary.outflow_minor, 350000);
+}
+
+#[test]
+#[should_panic(expected = "[inconsistent] sum of transaction details does not match")]
+fn test_batch_sum_mismatch() {
+    let camt = camt53_batch("3500.00", 2, &["1000.00", "1500.00"]);
+    check_batch(&camt.stmts[0].ntries[0]);
+}
+
+#[test]
+#[should_panic(expected = "has 2 transaction details, expected 3")]
+fn test_batch_incomplete() {
+    let camt = camt53_batch("2500.00", 3, &["1000.00", "1500.00"]);
+    check_batch(&camt.stmts[0].ntries[0]);
+}
+
+#[test]
+#[should_panic(
+    expected = "[parse] batch entry of 2500.00 CHF has transaction details without an amount in CHF"
+)]
+fn test_batch_other_currency() {
+    let mut camt = camt53_batch("2500.00", 2, &["1000.00", "1500.00"]);
+    camt.stmts[0].ntries[0].txDtls[1].AmtCcy = "EUR".to_string();
+    check_batch(&camt.stmts[0].ntries[0]);
+}
+
+#[test]
+fn test_single_tx_dtls_without_amount() {
+    // not a batch, the amount of the entry is the one of its only transaction
+    let mut camt = camt53_batch("1000.00", 1, &[""]);
+    camt.stmts[0].ntries[0].btchNbOfTxs = None;
+    check_batch(&camt.stmts[0].ntries[0]);
+}
+
+/// statement with two entries and the given <TxsSummry>
+fn camt53_txs_summry(txs_summry: &str) -> Document {
+    parse_camt53(&format!(
+        concat!(
+            "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><Stmt>",
+            "<ElctrncSeqNb>9</ElctrncSeqNb><TxsSummry>{}</TxsSummry>",
+            "<Ntry><Amt Ccy=\"CHF\">100.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts>BOOK</Sts></Ntry>",
+            "<Ntry><Amt Ccy=\"CHF\">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts></Ntry>",
+            "</Stmt></BkToCstmrStmt></Document>"
+        ),
+        txs_summry
+    )).unwrap()
+}
+
+#[test]
+fn test_txs_summry() {
+    let camt = camt53_txs_summry(concat!(
+        "<TtlNtries><NbOfNtries>2</NbOfNtries><Sum>100.05</Sum></TtlNtries>",
+        "<TtlCdtNtries><NbOfNtries>1</NbOfNtries><Sum>100</Sum></TtlCdtNtries>",
+        "<TtlDbtNtries><NbOfNtries>1</NbOfNtries><Sum>0.05</Sum></TtlDbtNtries>"
+    ));
+    let totals = check_txs_summry(&camt.stmts[0]);
+    assert_eq!(
+        totals,
+        EntryTotals {
+            nb_of_ntries: 2,
+            sum_minor: 10005,
+            cdt_nb_of_ntries: 1,
+            cdt_sum_minor: 10000,
+            dbt_nb_of_ntries: 1,
+            dbt_sum_minor: 5,
+        }
+    );
+    assert_eq!(
+        totals.commitment(true),
+        r#"{"nb_of_ntries":2,"sum_minor":10005,"cdt_nb_of_ntries":1,"cdt_sum_minor":10000,"dbt_nb_of_ntries":1,"dbt_sum_minor":5,"checked":true}"#
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "TxsSummry TtlDbtNtries/NbOfNtries of statement 9 is 2 but entries give 1"
+)]
+fn test_txs_summry_mismatch() {
+    let camt = camt53_txs_summry("<TtlDbtNtries><NbOfNtries>2</NbOfNtries></TtlDbtNtries>");
+    check_txs_summry(&camt.stmts[0]);
+}
+
+#[test]
+#[should_panic(expected = "[parse] invalid credit debit indicator 'RVSL' for amount 0.05")]
+fn test_txs_summry_invalid_indicator() {
+    let mut camt = camt53_txs_summry("");
+    camt.stmts[0].ntries[1].cdtDbtInd = "RVSL".to_string();
+    check_txs_summry(&camt.stmts[0]);
+}
+
+#[test]
+#[should_panic(expected = "[parse] sum of the amounts is too large")]
+fn test_txs_summry_overflow() {
+    let mut camt = camt53_txs_summry("");
+    camt.stmts[0].ntries[0].amt = "92233720368547758.07".to_string();
+    check_txs_summry(&camt.stmts[0]);
+}
+
+#[test]
+fn test_bk_tx_cd() {
+    let camt = parse_camt53(concat!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt><Stmt>",
+        "<Ntry><Amt Ccy=\"CHF\">100.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts>BOOK</Sts>",
+        "<BkTxCd><Domn><Cd>PMNT</Cd><Fmly><Cd>RCDT</Cd><SubFmlyCd>ESCT</SubFmlyCd></Fmly></Domn></BkTxCd>",
+        "<NtryDtls><TxDtls><BkTxCd><Domn><Cd>XTND</Cd></Domn></BkTxCd></TxDtls></NtryDtls></Ntry>",
+        "<Ntry><Amt Ccy=\"CHF\">30.00</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts>",
+        "<BkTxCd><Domn><Cd>PMNT</Cd><Fmly><Cd>CCRD</Cd><SubFmlyCd>POSD</SubFmlyCd></Fmly></Domn></BkTxCd></Ntry>",
+        "<Ntry><Amt Ccy=\"CHF\">0.05</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts>",
+        "<BkTxCd><Prtry><Cd>FCHG</Cd><Issr>SWIFT</Issr></Prtry></BkTxCd></Ntry>",
+        "</Stmt></BkToCstmrStmt></Document>"
+    )).unwrap();
+    let ntries = &camt.stmts[0].ntries;
+    // the code of the transaction details does not overwrite the one of the entry
+    assert_eq!(ntries[0].bkTxCd.code(), "PMNT/RCDT/ESCT");
+    assert_eq!(ntries[1].bkTxCd.code(), "PMNT/CCRD/POSD");
+    assert_eq!(ntries[2].bkTxCd.code(), "");
+    assert_eq!(ntries[2].bkTxCd.prtry, "FCHG");
+
+    let summary = |codes: &[&str]| {
+        let codes: Vec<String> = codes.iter().map(|code| code.to_string()).collect();
+        EntrySummary::of(&camt.stmts[0], &TxCodeFilter::parse(&codes))
+    };
+    assert_eq!(summary(&[]).booked, 3);
+    let income = summary(&["PMNT/RCDT"]);
+    assert_eq!(
+        (income.booked, income.inflow_minor, income.outflow_minor),
+        (1, 10000, 0)
+    );
+    let expenses = summary(&["PMNT/CCRD/POSD", "PRTRY:FCHG"]);
+    assert_eq!(
+        (
+            expenses.booked,
+            expenses.inflow_minor,
+            expenses.outflow_minor
+        ),
+        (2, 0, 3005)
+    );
+    // a family code does not match a longer family name
+    assert_eq!(summary(&["PMNT/RC"]).booked, 0);
+    assert_eq!(
+        TxCodeFilter::parse(&[" PMNT/RCDT".to_string(), "".to_string()]).codes,
+        vec!["PMNT/RCDT".to_string()]
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid bank transaction code")]
+fn test_bk_tx_cd_invalid_filter() {
+    TxCodeFilter::parse(&["PMNT//ESCT".to_string()]);
+}
+
+const POLICY: &str = concat!(
+    "# hide the owner\nsalt=4f1c0e6b2a93d8e5b1f07c2a6d9e3b48\nstmts.iban=hash\n",
+    "stmts.othr_id=hash\naccounts=hash\nbind_to=hash\npayments.msg_id=hash\n",
+    "payments.orgnl_msg_id=omit\npayments.pmt_inf_id=omit\nreserves.pub_bank_fingerprint=omit\n",
+    "payments.ctrl_sum=omit\npub_client_pem=omit\n"
+);
+
+#[test]
+fn test_policy() {
+    let policy = Policy::parse(POLICY);
+    assert_eq!(policy.disclosure("stmts.iban"), policy::Disclosure::Hash);
+    assert_eq!(
+        policy.disclosure("stmts.balances"),
+        policy::Disclosure::Clear
+    );
+    assert_eq!(policy.hash().len(), 64);
+
+    let iban_hash = policy.salted_hash("CH4308307000289537312");
+    assert_eq!(
+        iban_hash,
+        hex::encode(
+            Impl::hash_bytes(b"4f1c0e6b2a93d8e5b1f07c2a6d9e3b48CH4308307000289537312").as_bytes()
+        )
+    );
+    assert_eq!(
+        json_object(vec![
+            policy.string("stmts.iban", "CH4308307000289537312"),
+            policy.string("stmts.elctrnc_seq_nb", "247"),
+            policy.string("pub_client_pem", "-----BEGIN PUBLIC KEY-----"),
+            policy.json("stmts.ntries", "{}"),
+        ]),
+        format!(
+            r#"{{"iban":"{}","elctrnc_seq_nb":"247","ntries":{{}}}}"#,
+            iban_hash
+        )
+    );
+
+    let payment = pain002::PaymentStatus {
+        msg_id: "STS-1".to_string(),
+        ctrl_sum: "10.00".to_string(),
+        ..Default::default()
+    };
+    assert!(!payment_commitment(&payment, &policy).contains("ctrl_sum"));
+    assert!(!payment_commitment(&payment, &policy).contains("STS-1"));
+
+    // without policy everything is in cleartext
+    let policy = Policy::parse("");
+    assert_eq!(policy.hash(), "");
+    assert_eq!(
+        policy.strings("accounts", &["CH4308307000289537312".to_string()]),
+        Some(r#""accounts":["CH4308307000289537312"]"#.to_string())
+    );
+}
+
+#[test]
+#[should_panic(expected = "[parse] policy hides stmts.iban but commits bind_to in cleartext")]
+fn test_policy_iban_hidden_bind_to_clear() {
+    Policy::parse(&POLICY.replace("bind_to=hash", "bind_to=clear"));
+}
+
+#[test]
+#[should_panic(expected = "field 'stmts.balances' can not be hashed")]
+fn test_policy_hash_not_allowed() {
+    Policy::parse("salt=4f1c0e6b2a93d8e5b1f07c2a6d9e3b48\nstmts.balances=hash");
+}
+
+#[test]
+#[should_panic(expected = "needs a salt")]
+fn test_policy_without_salt() {
+    Policy::parse("stmts.iban=hash");
+}
+
+#[test]
+#[should_panic(expected = "unknown field 'stmts.ntries.booked' in policy")]
+fn test_policy_unknown_field() {
+    Policy::parse("stmts.ntries.booked=omit");
+}
+
+#[test]
+fn test_policy_fingerprint() {
+    let policy = Policy::parse("pub_witness_pems=fingerprint\npub_bank_pem=fingerprint");
+    let der = parse(WITNESS_PUBLIC_KEY).expect("Failed to parse PEM");
+    let fingerprint = hex::encode(Impl::hash_bytes(der.contents()).as_bytes());
+    assert_eq!(policy::fingerprint(WITNESS_PUBLIC_KEY), fingerprint);
+    assert_eq!(
+        policy.strings("pub_witness_pems", &[WITNESS_PUBLIC_KEY.to_string()]),
+        Some(format!(r#""pub_witness_pems":["{}"]"#, fingerprint))
+    );
+}
+
+#[test]
+#[should_panic(expected = "field 'stmts.iban' is not a key")]
+fn test_policy_fingerprint_not_a_key() {
+    Policy::parse("stmts.iban=fingerprint");
+}
+
+const FX_RATES: &str = "date=2023-11-29\nEUR/CHF=0.9512\nJPY/CHF=0.0061\n";
+
+/// closing balances of three accounts in CHF, EUR and JPY
+fn fx_documents() -> Vec<Document> {
+    let bal = |amt: &str, ccy: &str, cdt_dbt_ind: &str, dt: &str| {
+        format!(
+            "<Bal><Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp><Amt Ccy=\"{}\">{}</Amt><CdtDbtInd>{}</CdtDbtInd><Dt><Dt>{}</Dt></Dt></Bal>",
+            ccy, amt, cdt_dbt_ind, dt
+        )
+    };
+    let stmt = |iban: &str, balance: String| {
+        format!(
+            "<Stmt><Acct><Id><IBAN>{}</IBAN></Id></Acct>{}</Stmt>",
+            iban, balance
+        )
+    };
+    vec![parse_camt53(&format!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:camt.053.001.04\"><BkToCstmrStmt>{}{}{}{}</BkToCstmrStmt></Document>",
+        stmt("CH4308307000289537312", bal("200.00", "CHF", "CRDT", "2023-11-29")),
+        stmt("CH4308307000289537312", bal("100.00", "CHF", "CRDT", "2023-11-28")),
+        stmt("DE89370400440532013000", bal("50.00", "EUR", "DBIT", "2023-11-29")),
+        stmt("GB82WEST12345698765432", bal("1000", "JPY", "CRDT", "2023-11-29")),
+    )).unwrap()]
+}
+
+fn fx_signature(rates: &str) -> Vec<u8> {
+    let witness_key = RsaPrivateKey::from_pkcs8_pem(WITNESS_PRIVATE_KEY).unwrap();
+    witness_key
+        .sign(
+            Pkcs1v15Sign::new::<RsaSha256>(),
+            Impl::hash_bytes(rates.as_bytes()).as_bytes(),
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_fx_total() {
+    let rates = fx::RateSet::parse(FX_RATES);
+    rates.verify(
+        &WitnessKey::from_pem(WITNESS_PUBLIC_KEY, WitnessScheme::RsaPkcs1),
+        WitnessScheme::RsaPkcs1,
+        &fx_signature(FX_RATES),
+    );
+    let total = fx::FxTotal::of(&fx_documents(), &rates, "CHF", "");
+    // 200.00 CHF (latest closing balance) - 50.00 EUR * 0.9512 + 1000 JPY * 0.0061
+    assert_eq!(total.total_minor, 20000 - 4756 + 610);
+    assert_eq!(total.accounts, 3);
+    assert_eq!(total.rate_date, "2023-11-29");
+    assert_eq!(
+        total.rates_hash,
+        hex::encode(Impl::hash_bytes(FX_RATES.as_bytes()).as_bytes())
+    );
+    assert!(total
+        .commitment("")
+        .starts_with(r#"{"reporting_ccy":"CHF","ccy_exp":2,"total_minor":15854,"accounts":3,"#));
+}
+
+#[test]
+fn test_fx_key_without_rates() {
+    // all accounts in the reporting currency, no signature was verified with the key
+    let total = fx::FxTotal {
+        reporting_ccy: "CHF".to_string(),
+        ..Default::default()
+    };
+    assert!(total
+        .commitment(WITNESS_PUBLIC_KEY)
+        .ends_with(r#""rates_hash":"","fx_key_fingerprint":""}"#));
+}
+
+#[test]
+#[should_panic(expected = "FX rate set signature invalid")]
+fn test_fx_rates_tampered() {
+    let rates = fx::RateSet::parse(&FX_RATES.replace("0.9512", "1.9512"));
+    rates.verify(
+        &WitnessKey::from_pem(WITNESS_PUBLIC_KEY, WitnessScheme::RsaPkcs1),
+        WitnessScheme::RsaPkcs1,
+        &fx_signature(FX_RATES),
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "[verification] FX rates of '2023-11-28' are not of the closing balances of 2023-11-29"
+)]
+fn test_fx_rates_other_date() {
+    let rates = fx::RateSet::parse(&FX_RATES.replace("2023-11-29", "2023-11-28"));
+    fx::FxTotal::of(&fx_documents(), &rates, "CHF", "");
+}
+
+#[test]
+fn test_fx_total_reserves_date() {
+    // only the CHF account has a closing balance up to the date of the reserves
+    let rates = fx::RateSet::parse(&FX_RATES.replace("2023-11-29", "2023-11-28"));
+    let total = fx::FxTotal::of(&fx_documents(), &rates, "CHF", "2023-11-28");
+    assert_eq!((total.total_minor, total.accounts), (10000, 1));
+}
+
+#[test]
+#[should_panic(expected = "no FX rate for CHF/USD")]
+fn test_fx_rate_missing() {
+    fx::FxTotal::of(&fx_documents(), &fx::RateSet::parse(FX_RATES), "USD", "");
+}
+
+#[test]
+fn test_reserves() {
+    let documents = fx_documents();
+    let reserves = reserves::Reserves::of(
+        &documents,
+        "2023-11-29",
+        &["CHF=150.00".to_string(), "EUR=10".to_string()],
+    );
+    assert_eq!(
+        reserves.totals,
+        vec![
+            reserves::Total {
+                ccy: "CHF".to_string(),
+                total_minor: 20000,
+                accounts: 1
+            },
+            reserves::Total {
+                ccy: "EUR".to_string(),
+                total_minor: -5000,
+                accounts: 1
+            },
+            reserves::Total {
+                ccy: "JPY".to_string(),
+                total_minor: 1000,
+                accounts: 1
+            },
+        ]
+    );
+    assert_eq!(
+        reserves.commitment(&[]),
+        concat!(
+            r#"{"date":"2023-11-29","oldest_dt":"2023-11-29","banks":[],"totals":["#,
+            r#"{"ccy":"CHF","ccy_exp":2,"total_minor":20000,"accounts":1},"#,
+            r#"{"ccy":"EUR","ccy_exp":2,"total_minor":-5000,"accounts":1},"#,
+            r#"{"ccy":"JPY","ccy_exp":0,"total_minor":1000,"accounts":1}],"liabilities":["#,
+            r#"{"ccy":"CHF","amt_minor":15000,"covered":true},"#,
+            r#"{"ccy":"EUR","amt_minor":1000,"covered":false}]}"#
+        )
+    );
+
+    // balances after the date are not used
+    let reserves = reserves::Reserves::of(&documents, "2023-11-28", &[]);
+    assert_eq!(reserves.totals.len(), 1);
+    assert_eq!(reserves.totals[0].total_minor, 10000);
+    assert_eq!(reserves.oldest_dt, "2023-11-28");
+}
+
+#[test]
+#[should_panic(expected = "no closing balance up to 2023-11-27")]
+fn test_reserves_no_balance() {
+    reserves::Reserves::of(&fx_documents(), "2023-11-27", &[]);
+}
@@ -6,7 +6,10 @@
 //! //! df
 //! ```
 
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
+use hyperfridge_ebics::HyperfridgeError;
+use risc0_zkvm::sha::{Impl, Sha256};
 use risc0_zkvm::Receipt;
 use serde::Deserialize;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -35,49 +38,261 @@ macro_rules! v {
 /// ```json
 /// {
 ///     "hostinfo": "host:main",
-///     "iban": "CH4308307000289537312",
+///     "proof": "full",
+///     "policy_hash": "",
+///     "challenge": "",
+///     "bind_to": "",
+///     "bind_checked": false,
+///     "include_pending": false,
+///     "tx_codes": [],
+///     "order_type": "",
+///     "bank_timestamp": "2023-11-30T08:38:11.8835379Z",
+///     "accounts": ["CH4308307000289537312"],
 ///     "stmts": [
 ///         {
+///             "msg_version": "camt.053.001.04",
+///             "iban": "CH4308307000289537312",
+///             "othr_id": "",
+///             "othr_schme_nm": "",
+///             "acct_ccy": "",
 ///             "elctrnc_seq_nb": "247",
 ///             "fr_dt_tm": "2023-11-29T00:00:00",
 ///             "to_dt_tm": "2023-11-29T00:00:00",
-///             "amt": "31709.14",
-///             "ccy": "CHF",
-///             "cd": "OPBD"
+///             "balances": [
+///                 {
+///                     "cd": "OPBD",
+///                     "amt": "31709.14",
+///                     "amt_minor": 3170914,
+///                     "ccy": "CHF",
+///                     "ccy_exp": 2,
+///                     "cdt_dbt_ind": "CRDT",
+///                     "dt": "2023-11-29"
+///                 }
+///             ],
+///             "ntries": {
+///                 "booked": 1,
+///                 "pending": 0,
+///                 "info": 0,
+///                 "reversed": 0,
+///                 "txs": 1,
+///                 "ccy": "CHF",
+///                 "inflow_minor": 0,
+///                 "outflow_minor": 5
+///             },
+///             "txs_summry": {
+///                 "nb_of_ntries": 1,
+///                 "sum_minor": 5,
+///                 "cdt_nb_of_ntries": 0,
+///                 "cdt_sum_minor": 0,
+///                 "dbt_nb_of_ntries": 1,
+///                 "dbt_sum_minor": 5,
+///                 "checked": false
+///             }
 ///         },
 ///         {
+///             "msg_version": "camt.053.001.04",
+///             "iban": "CH4308307000289537312",
+///             "othr_id": "",
+///             "othr_schme_nm": "",
+///             "acct_ccy": "",
 ///             "elctrnc_seq_nb": "248",
 ///             "fr_dt_tm": "2023-11-30T00:00:00",
 ///             "to_dt_tm": "2023-11-30T00:00:00",
-///             "amt": "31709.09",
-///             "ccy": "CHF",
-///             "cd": "OPBD"
+///             "balances": [
+///                 {
+///                     "cd": "OPBD",
+///                     "amt": "31709.09",
+///                     "amt_minor": 3170909,
+///                     "ccy": "CHF",
+///                     "ccy_exp": 2,
+///                     "cdt_dbt_ind": "CRDT",
+///                     "dt": "2023-11-30"
+///                 }
+///             ],
+///             "ntries": {
+///                 "booked": 1,
+///                 "pending": 0,
+///                 "info": 0,
+///                 "reversed": 0,
+///                 "txs": 1,
+///                 "ccy": "CHF",
+///                 "inflow_minor": 0,
+///                 "outflow_minor": 5
+///             },
+///             "txs_summry": {
+///                 "nb_of_ntries": 1,
+///                 "sum_minor": 5,
+///                 "cdt_nb_of_ntries": 0,
+///                 "cdt_sum_minor": 0,
+///                 "dbt_nb_of_ntries": 1,
+///                 "dbt_sum_minor": 5,
+///                 "checked": false
+///             }
 ///         }
-///     ]
+///     ],
+///     "payments": [],
+///     "reserves": null,
+///     "fx": null
 /// }
 /// ```
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
 #[allow(dead_code)]
 struct Commitment {
     hostinfo: String,
-    iban: String,
+    proof: String,
+    policy_hash: String,
+    challenge: String,
+    bind_to: String,
+    bind_checked: bool,
+    include_pending: bool,
+    tx_codes: Vec<String>,
+    order_type: String,
+    bank_timestamp: String,
+    accounts: Vec<String>,
+    witness_keys_hash: String,
+    witness_threshold: usize,
     stmts: Vec<Stmt>,
+    payments: Vec<Payment>,
+    reserves: Option<Reserves>,
+    fx: Option<FxTotal>,
+}
+
+/// Struct for the total of the closing balances in the reporting currency
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct FxTotal {
+    reporting_ccy: String,
+    ccy_exp: u32,
+    total_minor: i64,
+    accounts: usize,
+    rate_date: String,
+    rates_hash: String,
+    fx_key_fingerprint: String,
+}
+
+/// Struct for the proof of reserves: totals of the closing balances up to a date per currency
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct Reserves {
+    date: String,
+    oldest_dt: String,
+    banks: Vec<ReservesBank>,
+    totals: Vec<ReservesTotal>,
+    liabilities: Vec<Liability>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct ReservesBank {
+    pub_bank_fingerprint: String,
+    bank_timestamp: String,
+    order_type: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct ReservesTotal {
+    ccy: String,
+    ccy_exp: u32,
+    total_minor: i64,
+    accounts: usize,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct Liability {
+    ccy: String,
+    amt_minor: i64,
+    covered: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
 #[allow(dead_code)]
 struct Stmt {
+    msg_version: String,
+    iban: String,
+    othr_id: String,
+    othr_schme_nm: String,
+    acct_ccy: String,
     elctrnc_seq_nb: String,
     fr_dt_tm: String,
     to_dt_tm: String,
-    amt: String,
+    balances: Vec<Balance>,
+    ntries: EntrySummary,
+    txs_summry: EntryTotals,
+}
+
+/// Struct for the totals of all entries of a statement, checked against <TxsSummry>
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct EntryTotals {
+    nb_of_ntries: usize,
+    sum_minor: i64,
+    cdt_nb_of_ntries: usize,
+    cdt_sum_minor: i64,
+    dbt_nb_of_ntries: usize,
+    dbt_sum_minor: i64,
+    checked: bool,
+}
+
+/// Struct for the entries of a statement in the commitment - counts per category and
+/// the sum of booked (and optionally pending) entries in minor units
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct EntrySummary {
+    booked: usize,
+    pending: usize,
+    info: usize,
+    reversed: usize,
+    txs: usize,
     ccy: String,
+    inflow_minor: i64,
+    outflow_minor: i64,
+}
+
+/// Struct for the status of an uploaded payment (pain.002) in the commitment
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct Payment {
+    level: String,
+    msg_id: String,
+    orgnl_msg_id: String,
+    pmt_inf_id: String,
+    orgnl_nb_of_txs: String,
+    ctrl_sum: String,
+    listed_txs: usize,
+    listed_amt_minor: i64,
+    ccy: String,
+    sts: String,
+}
+
+/// Struct for the balances of a statement in the commitment
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(dead_code)]
+struct Balance {
     cd: String,
+    amt: String,
+    amt_minor: i64,
+    ccy: String,
+    ccy_exp: u32,
+    cdt_dbt_ind: String,
+    dt: String,
 }
 
 /// Verify a receipt. If no parameters are presented, use standard values
 /// generated by test cases which are included in release.
-///verbose
+/// Exits with the exit code of the `HyperfridgeError` if the receipt is not valid.
 fn main() {
     let cli = parse_cli();
 
@@ -86,46 +301,387 @@ fn main() {
         std::process::exit(0);
     }
 
+    if let Err(error) = verify(&cli) {
+        eprintln!("{}", error);
+        std::process::exit(error.exit_code());
+    }
+}
+
+fn verify(cli: &Cli) -> Result<(), HyperfridgeError> {
     let proof_json_path: PathBuf;
     let image_id_hex;
+    let mut expected_challenge: Option<String> = None;
+    let mut max_age_secs: Option<i64> = None;
+    let mut expected_bind_to: Option<String> = None;
+    let mut bind_to_salt: Option<String> = None;
+    let mut policy_path: Option<PathBuf> = None;
+    let mut candidate_iban: Option<(String, String)> = None;
+    let mut expected_proof: Option<String> = None;
+    let mut expected_witness_keys_hash: Option<String> = None;
+    let mut min_witness_threshold: Option<usize> = None;
+    let mut expected_fx_key_fingerprint: Option<String> = None;
 
     match &cli.command {
         Some(Commands::Verify {
             imageid_hex,
             proof_json,
+            challenge,
+            max_age,
+            bind_to,
+            salt,
+            policy,
+            proof,
+            witness_keys_hash,
+            min_witness_threshold: min_threshold,
+            fx_key_fingerprint,
         }) => {
             println!("verify {} {:?}", imageid_hex, proof_json);
             image_id_hex = imageid_hex.clone();
+            expected_challenge = challenge.clone();
+            max_age_secs = *max_age;
+            expected_bind_to = bind_to.clone();
+            bind_to_salt = salt.clone();
+            policy_path = policy.clone();
+            expected_proof = proof.clone();
+            expected_witness_keys_hash = witness_keys_hash.clone();
+            min_witness_threshold = *min_threshold;
+            expected_fx_key_fingerprint = fx_key_fingerprint.clone();
             proof_json_path = proof_json
                 .clone()
-                .unwrap_or_else(|| panic!("Proof JSON not provided"));
+                .ok_or_else(|| HyperfridgeError::Input("Proof JSON not provided".to_string()))?;
+        }
+        Some(Commands::CheckIban {
+            imageid_hex,
+            proof_json,
+            iban,
+            salt,
+        }) => {
+            image_id_hex = imageid_hex.clone();
+            candidate_iban = Some((iban.clone(), salt.clone()));
+            proof_json_path = proof_json.clone();
         }
         Some(Commands::Test) => {
-            image_id_hex = get_image_id_hex_from_file();
+            image_id_hex = get_image_id_hex_from_file()?;
             proof_json_path = PathBuf::from(format!(
                 "../data/test/test.xml-Receipt-{}-latest.json",
                 image_id_hex
             ));
         }
         None => {
-            panic!("No command given");
+            return Err(HyperfridgeError::Input("No command given".to_string()));
+        }
+    }
+
+    let receipt_json: Vec<u8> = fs::read(&proof_json_path).map_err(|e| {
+        HyperfridgeError::Io(format!(
+            "Failed to read file at {:?} {}",
+            proof_json_path.to_str(),
+            e
+        ))
+    })?;
+
+    let receipt: Receipt = serde_json::from_slice(&receipt_json)
+        .map_err(|e| HyperfridgeError::Parse(format!("Failed to parse proof JSON {}", e)))?;
+
+    let image_id_array = hex_to_u32_array(&image_id_hex).map_err(|e| {
+        HyperfridgeError::Input(format!("Failed to convert hex to u32 array {}", e))
+    })?;
+
+    receipt.verify(image_id_array).map_err(|e| {
+        HyperfridgeError::Verification(format!(
+            "verify failed with image id: {} {}",
+            &image_id_hex, e
+        ))
+    })?;
+
+    let commitment = get_commitment(receipt)?;
+    v!("{} proof with image ID {}", commitment.proof, &image_id_hex);
+    if let Some(expected_proof) = expected_proof {
+        check_proof(&commitment, &expected_proof)?;
+    }
+
+    // freshness: the receipt needs to be created for our challenge and on recent bank data
+    if let Some(expected_challenge) = expected_challenge {
+        check_challenge(&commitment, &expected_challenge)?;
+    }
+    if let Some(max_age_secs) = max_age_secs {
+        check_max_age(&commitment, max_age_secs, Utc::now())?;
+    }
+    if let Some(expected_bind_to) = expected_bind_to {
+        check_binding(&commitment, &expected_bind_to, bind_to_salt.as_deref())?;
+    }
+    // the witnesses are chosen by the prover, pin them to the agreed key set
+    if let Some(expected_witness_keys_hash) = expected_witness_keys_hash {
+        check_witness_keys(&commitment, &expected_witness_keys_hash)?;
+    }
+    if let Some(min_witness_threshold) = min_witness_threshold {
+        check_witness_threshold(&commitment, min_witness_threshold)?;
+    }
+    // the rate provider is chosen by the prover as well
+    if let Some(expected_fx_key_fingerprint) = expected_fx_key_fingerprint {
+        check_fx_key(&commitment, &expected_fx_key_fingerprint)?;
+    }
+    if let Some(policy_path) = policy_path {
+        let policy = fs::read_to_string(&policy_path).map_err(|e| {
+            HyperfridgeError::Io(format!(
+                "Failed to read policy file {:?} {}",
+                policy_path, e
+            ))
+        })?;
+        check_policy(&commitment, &policy)?;
+    }
+    if let Some((iban, salt)) = candidate_iban {
+        check_iban(&commitment, &iban, &salt)?;
+    }
+
+    print_balances(&commitment);
+    println!("{:?}", commitment);
+    Ok(())
+}
+
+/// one line per balance: account, statement, balance type, date and signed amount
+fn print_balances(commitment: &Commitment) {
+    for stmt in commitment.stmts.iter() {
+        for balance in stmt.balances.iter() {
+            v!(
+                "{} #{} {} {} {}{} {}",
+                if stmt.iban.is_empty() {
+                    &stmt.othr_id
+                } else {
+                    &stmt.iban
+                },
+                stmt.elctrnc_seq_nb,
+                balance.cd,
+                balance.dt,
+                if balance.cdt_dbt_ind == "DBIT" {
+                    "-"
+                } else {
+                    ""
+                },
+                balance.amt,
+                balance.ccy
+            );
+        }
+    }
+    if let Some(reserves) = &commitment.reserves {
+        for total in reserves.totals.iter() {
+            v!(
+                "reserves {} of {} accounts {} minor units {}",
+                reserves.date,
+                total.accounts,
+                total.total_minor,
+                total.ccy
+            );
         }
+        for liability in reserves.liabilities.iter() {
+            v!(
+                "liability {} minor units {} {}",
+                liability.amt_minor,
+                liability.ccy,
+                if liability.covered {
+                    "covered"
+                } else {
+                    "NOT covered"
+                }
+            );
+        }
+    }
+    if let Some(fx) = &commitment.fx {
+        v!(
+            "total of {} accounts {} minor units {} (rates of {})",
+            fx.accounts,
+            fx.total_minor,
+            fx.reporting_ccy,
+            fx.rate_date
+        );
+    }
+}
+
+/// Receipt needs to be created by the guest image of the expected proof type
+fn check_proof(commitment: &Commitment, expected_proof: &str) -> Result<(), HyperfridgeError> {
+    if commitment.proof != expected_proof {
+        return Err(HyperfridgeError::Verification(format!(
+            "receipt is a {} proof and not a {} proof",
+            commitment.proof, expected_proof
+        )));
     }
+    v!("proof type matches");
+    Ok(())
+}
 
-    let receipt_json: Vec<u8> = fs::read(&proof_json_path)
-        .unwrap_or_else(|_| panic!("Failed to read file at {:?}", proof_json_path.to_str()));
+/// Receipt needs to commit the challenge of this verification request
+fn check_challenge(
+    commitment: &Commitment,
+    expected_challenge: &str,
+) -> Result<(), HyperfridgeError> {
+    if commitment.challenge != expected_challenge {
+        return Err(HyperfridgeError::Verification(format!(
+            "challenge in receipt '{}' does not match expected challenge '{}'",
+            commitment.challenge, expected_challenge
+        )));
+    }
+    v!("challenge matches");
+    Ok(())
+}
 
-    let receipt: Receipt =
-        serde_json::from_slice(&receipt_json).expect("Failed to parse proof JSON");
+/// Receipt needs to be bound to the expected on-chain identity, and the guest must have
+/// found the transfer proving that the account holder controls the IBAN. With a salt the
+/// identity is committed as salted hash (host --hash-iban).
+fn check_binding(
+    commitment: &Commitment,
+    expected_bind_to: &str,
+    salt: Option<&str>,
+) -> Result<(), HyperfridgeError> {
+    let committed = match salt {
+        Some(salt) => sha256_hex(&format!("{}{}", salt, expected_bind_to)),
+        None => expected_bind_to.to_string(),
+    };
+    if commitment.bind_to != committed {
+        return Err(HyperfridgeError::Verification(format!(
+            "receipt is bound to '{}' and not to '{}'",
+            commitment.bind_to, expected_bind_to
+        )));
+    }
+    if !commitment.bind_checked {
+        return Err(HyperfridgeError::BindingNotProven(format!(
+            "binding to '{}' has not been checked against a transfer",
+            expected_bind_to
+        )));
+    }
+    v!("receipt is bound to {}", expected_bind_to);
+    Ok(())
+}
+
+/// Receipt needs to be created with the witness keys agreed with the prover, see
+/// `witness_keys_hash` of the commitment
+fn check_witness_keys(
+    commitment: &Commitment,
+    expected_witness_keys_hash: &str,
+) -> Result<(), HyperfridgeError> {
+    if !commitment
+        .witness_keys_hash
+        .eq_ignore_ascii_case(expected_witness_keys_hash)
+    {
+        return Err(HyperfridgeError::Verification(format!(
+            "receipt was created with witness keys '{}' and not with '{}'",
+            commitment.witness_keys_hash, expected_witness_keys_hash
+        )));
+    }
+    v!("witness keys match");
+    Ok(())
+}
 
-    let image_id_array =
-        hex_to_u32_array(&image_id_hex).expect("Failed to convert hex to u32 array");
+/// The guest checked at least `min_witness_threshold` witness signatures
+fn check_witness_threshold(
+    commitment: &Commitment,
+    min_witness_threshold: usize,
+) -> Result<(), HyperfridgeError> {
+    if commitment.witness_threshold < min_witness_threshold {
+        return Err(HyperfridgeError::Verification(format!(
+            "receipt requires {} witness signatures, at least {} expected",
+            commitment.witness_threshold, min_witness_threshold
+        )));
+    }
+    v!("witness threshold is {}", commitment.witness_threshold);
+    Ok(())
+}
+
+/// The FX rates of the total in the reporting currency need to be signed by the expected
+/// rate provider
+fn check_fx_key(
+    commitment: &Commitment,
+    expected_fx_key_fingerprint: &str,
+) -> Result<(), HyperfridgeError> {
+    let fx = commitment.fx.as_ref().ok_or_else(|| {
+        HyperfridgeError::Verification("receipt has no total in a reporting currency".to_string())
+    })?;
+    if fx.fx_key_fingerprint.is_empty()
+        || !fx
+            .fx_key_fingerprint
+            .eq_ignore_ascii_case(expected_fx_key_fingerprint)
+    {
+        return Err(HyperfridgeError::Verification(format!(
+            "FX rates of the receipt are signed by '{}' and not by '{}'",
+            fx.fx_key_fingerprint, expected_fx_key_fingerprint
+        )));
+    }
+    v!("FX rate provider matches");
+    Ok(())
+}
+
+/// Receipt needs to be created with the disclosure policy agreed with the prover
+fn check_policy(commitment: &Commitment, policy: &str) -> Result<(), HyperfridgeError> {
+    let policy_hash = if policy.trim().is_empty() {
+        String::new()
+    } else {
+        sha256_hex(policy)
+    };
+    if commitment.policy_hash != policy_hash {
+        return Err(HyperfridgeError::Verification(format!(
+            "receipt was created with disclosure policy '{}' and not with '{}'",
+            commitment.policy_hash, policy_hash
+        )));
+    }
+    v!("disclosure policy matches");
+    Ok(())
+}
+
+/// IBAN committed as salted hash (host --hash-iban) must be the candidate IBAN
+fn check_iban(commitment: &Commitment, iban: &str, salt: &str) -> Result<(), HyperfridgeError> {
+    let iban_hash = sha256_hex(&format!("{}{}", salt, iban.replace(' ', "")));
+    let found = commitment.stmts.iter().any(|stmt| stmt.iban == iban_hash)
+        || commitment
+            .accounts
+            .iter()
+            .any(|account| *account == iban_hash);
+    if !found {
+        return Err(HyperfridgeError::IbanNotFound(format!(
+            "IBAN {} with the given salt is not in the receipt",
+            iban
+        )));
+    }
+    println!("IBAN {} is in the receipt", iban);
+    Ok(())
+}
 
-    receipt
-        .verify(image_id_array)
-        .unwrap_or_else(|_| panic!("verify failed with image id: {}", &image_id_hex));
+/// hex(sha256(value)) as used for salted hashes in the commitment
+fn sha256_hex(value: &str) -> String {
+    Impl::hash_bytes(value.as_bytes())
+        .as_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
 
-    println!("{:?}", get_commitment(receipt));
+/// Bank timestamps of all responses must not be older than `max_age_secs` - the response of
+/// the receipt and, for a proof of reserves, the responses of all further banks
+fn check_max_age(
+    commitment: &Commitment,
+    max_age_secs: i64,
+    now: DateTime<Utc>,
+) -> Result<(), HyperfridgeError> {
+    let reserves_banks = commitment.reserves.iter().flat_map(|r| r.banks.iter());
+    let mut oldest_age_secs = i64::MIN;
+    for bank_timestamp in std::iter::once(&commitment.bank_timestamp)
+        .chain(reserves_banks.map(|bank| &bank.bank_timestamp))
+    {
+        let parsed = DateTime::parse_from_rfc3339(bank_timestamp).map_err(|_| {
+            HyperfridgeError::Verification(format!(
+                "receipt has no valid bank timestamp '{}'",
+                bank_timestamp
+            ))
+        })?;
+        let age_secs = (now - parsed.with_timezone(&Utc)).num_seconds();
+        if age_secs > max_age_secs {
+            return Err(HyperfridgeError::Verification(format!(
+                "receipt is {}s old (bank timestamp {}), maximum age is {}s",
+                age_secs, bank_timestamp, max_age_secs
+            )));
+        }
+        oldest_age_secs = oldest_age_secs.max(age_secs);
+    }
+    v!("receipt is {}s old", oldest_age_secs);
+    Ok(())
 }
 
 fn hex_to_u32_array(hex_str: &str) -> Result<[u32; 8], &'static str> {
@@ -143,35 +699,33 @@ fn hex_to_u32_array(hex_str: &str) -> Result<[u32; 8], &'static str> {
 }
 
 /// Get Image ID as HEX
-fn get_image_id_hex_from_file() -> String {
+fn get_image_id_hex_from_file() -> Result<String, HyperfridgeError> {
     match fs::read_to_string(DEFAULT_ID_HEX) {
         Ok(file_content) => {
             v!(
                 "no image ID provided - using image ID from file  {}",
                 DEFAULT_ID_HEX
             );
-            file_content
+            Ok(file_content)
         }
-        Err(_) => {
-            match fs::read_to_string(FALLBACK_ID_HEX) {
-                Ok(file_content) => {
-                    v!(
-                        "no image id provided - using image ID from file  {}",
-                        FALLBACK_ID_HEX
-                    );
-                    file_content
-                }
-                Err(e) => {
-                    v!("Failed to read the fallback file: {}", e);
-                    std::process::exit(1); // Exit if unable to read the file
-                }
+        Err(_) => match fs::read_to_string(FALLBACK_ID_HEX) {
+            Ok(file_content) => {
+                v!(
+                    "no image id provided - using image ID from file  {}",
+                    FALLBACK_ID_HEX
+                );
+                Ok(file_content)
             }
-        }
+            Err(e) => Err(HyperfridgeError::Io(format!(
+                "Failed to read image ID from {} or {} {}",
+                DEFAULT_ID_HEX, FALLBACK_ID_HEX, e
+            ))),
+        },
     }
 }
 
 /// Read public commitment from receipt
-fn get_commitment(receipt: Receipt) -> Result<Commitment, serde_json::Error> {
+fn get_commitment(receipt: Receipt) -> Result<Commitment, HyperfridgeError> {
     // for some reason there are other characters at the beginning of the commitment remove that
     let commitment_string = {
         let bytes = &receipt.journal.bytes;
@@ -180,12 +734,18 @@ fn get_commitment(receipt: Receipt) -> Result<Commitment, serde_json::Error> {
             .iter()
             .rposition(|&b| b == b'}')
             .unwrap_or(bytes.len());
-        String::from_utf8(bytes[start_index..=end_index].to_vec())
-            .expect("Failed to convert bytes to string from journal in main")
+        String::from_utf8(bytes[start_index..=end_index].to_vec()).map_err(|e| {
+            HyperfridgeError::Parse(format!(
+                "Failed to convert bytes to string from journal {}",
+                e
+            ))
+        })?
     };
     v!("Commitments in receipt: {}", commitment_string);
 
-    serde_json::from_str(&commitment_string)
+    serde_json::from_str(&commitment_string).map_err(|e| {
+        HyperfridgeError::Parse(format!("Failed to parse commitment in receipt {}", e))
+    })
 }
 
 #[derive(Parser, Debug)]
@@ -226,6 +786,82 @@ enum Commands {
             required = true
         )]
         proof_json: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            help = "Expected challenge (nonce) - the receipt must have been created with host --challenge for this value."
+        )]
+        challenge: Option<String>,
+        #[arg(
+            short,
+            long,
+            help = "Maximum age in seconds of the bank response (TimestampBankParameter) the receipt is based on."
+        )]
+        max_age: Option<i64>,
+        #[arg(
+            short,
+            long,
+            help = "Expected on-chain identity - the receipt must have been created with host --bind-to for this value and --bind-check."
+        )]
+        bind_to: Option<String>,
+        #[arg(
+            long,
+            requires = "bind_to",
+            help = "Salt shared off-chain by the prover, for a receipt created with host --hash-iban: --bind-to is compared with the hashed bind_to."
+        )]
+        salt: Option<String>,
+        #[arg(
+            long,
+            help = "Disclosure policy file - the receipt must have been created with host --policy for this file.",
+            value_name = "FILE"
+        )]
+        policy: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Expected proof type (full, balance, binding or aggregates) - the receipt must have been created with host --proof for this type."
+        )]
+        proof: Option<String>,
+        #[arg(
+            long,
+            help = "Expected witness_keys_hash - the receipt must have been created with this set of --witnesskey and --witness-scheme."
+        )]
+        witness_keys_hash: Option<String>,
+        #[arg(
+            long,
+            help = "Minimum number of witness signatures the receipt must have been created with (host --witness-threshold)."
+        )]
+        min_witness_threshold: Option<usize>,
+        #[arg(
+            long,
+            help = "Expected fingerprint sha256(DER) of the FX rate provider key - the FX rates of the receipt must be signed with this key."
+        )]
+        fx_key_fingerprint: Option<String>,
+    },
+    /// Checks an IBAN and salt against a receipt created with host --hash-iban.
+    CheckIban {
+        #[arg(
+            short,
+            long,
+            help = "The image ID of the risk0 image the receipt was created with.",
+            required = true
+        )]
+        imageid_hex: String,
+        #[arg(
+            short,
+            long,
+            help = "Risc0 proof file as json, generated by hyperfridge binary.",
+            value_name = "FILE",
+            required = true
+        )]
+        proof_json: PathBuf,
+        #[arg(long, help = "Candidate IBAN, e.g. CH4308307000289537312.")]
+        iban: String,
+        #[arg(
+            short,
+            long,
+            help = "Salt shared off-chain by the prover, as given to host --salt."
+        )]
+        salt: String,
     },
     /// Uses test data - you may need RISC0_DEV_MODE=true environment variable
     Test,
@@ -236,3 +872,205 @@ fn parse_cli() -> Cli {
     VERBOSE.store(cli.verbose, Ordering::Relaxed);
     cli
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        check_binding, check_challenge, check_fx_key, check_iban, check_max_age, check_policy,
+        check_proof, check_witness_keys, check_witness_threshold, sha256_hex, Commitment,
+        HyperfridgeError, Stmt,
+    };
+    use chrono::{DateTime, Utc};
+
+    fn commitment(challenge: &str, bank_timestamp: &str) -> Commitment {
+        Commitment {
+            hostinfo: "test".to_string(),
+            proof: "full".to_string(),
+            policy_hash: String::new(),
+            challenge: challenge.to_string(),
+            bind_to: String::new(),
+            bind_checked: false,
+            include_pending: false,
+            tx_codes: Vec::new(),
+            order_type: String::new(),
+            bank_timestamp: bank_timestamp.to_string(),
+            accounts: vec!["CH4308307000289537312".to_string()],
+            witness_keys_hash: "5a1e".to_string(),
+            witness_threshold: 1,
+            stmts: Vec::new(),
+            payments: Vec::new(),
+            reserves: None,
+            fx: None,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2023-11-30T09:38:11Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn proof_matches() {
+        check_proof(&commitment("", ""), "full").unwrap();
+    }
+
+    #[test]
+    fn proof_mismatch() {
+        let err = check_proof(&commitment("", ""), "balance").unwrap_err();
+        assert!(matches!(err, HyperfridgeError::Verification(_)));
+        assert!(err.message().contains("not a balance proof"));
+    }
+
+    #[test]
+    fn challenge_matches() {
+        check_challenge(&commitment("n0nce", ""), "n0nce").unwrap();
+    }
+
+    #[test]
+    fn challenge_mismatch() {
+        let err = check_challenge(&commitment("old", ""), "n0nce").unwrap_err();
+        assert!(err.message().contains("does not match expected challenge"));
+    }
+
+    #[test]
+    fn fresh_receipt() {
+        check_max_age(&commitment("", "2023-11-30T08:38:11.8835379Z"), 3600, now()).unwrap();
+    }
+
+    #[test]
+    fn stale_receipt() {
+        let err =
+            check_max_age(&commitment("", "2023-11-30T08:38:11.8835379Z"), 60, now()).unwrap_err();
+        assert!(err.message().contains("maximum age is 60s"));
+        assert_eq!(err.exit_code(), 21);
+    }
+
+    #[test]
+    fn stale_reserves_bank() {
+        // the first response is fresh, the one of the second bank is a day old
+        let mut commitment = commitment("", "2023-11-30T08:38:11.8835379Z");
+        commitment.reserves = Some(Reserves {
+            banks: vec![
+                ReservesBank {
+                    bank_timestamp: "2023-11-30T08:38:11.8835379Z".to_string(),
+                    ..Default::default()
+                },
+                ReservesBank {
+                    bank_timestamp: "2023-11-29T08:00:00Z".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        });
+        let err = check_max_age(&commitment, 3600, now()).unwrap_err();
+        assert!(err.message().contains("2023-11-29T08:00:00Z"));
+    }
+
+    #[test]
+    fn binding_checked() {
+        let mut commitment = commitment("", "");
+        commitment.bind_to = "did:example:123".to_string();
+        commitment.bind_checked = true;
+        check_binding(&commitment, "did:example:123", None).unwrap();
+    }
+
+    #[test]
+    fn binding_hashed() {
+        let mut commitment = commitment("", "");
+        commitment.bind_to = sha256_hex("4f1c0e6b2a93d8e5did:example:123");
+        commitment.bind_checked = true;
+        check_binding(&commitment, "did:example:123", Some("4f1c0e6b2a93d8e5")).unwrap();
+        let err = check_binding(&commitment, "did:example:123", None).unwrap_err();
+        assert!(matches!(err, HyperfridgeError::Verification(_)));
+    }
+
+    #[test]
+    fn binding_unchecked() {
+        let mut commitment = commitment("", "");
+        commitment.bind_to = "did:example:123".to_string();
+        let err = check_binding(&commitment, "did:example:123", None).unwrap_err();
+        assert!(matches!(err, HyperfridgeError::BindingNotProven(_)));
+        assert!(err.message().contains("has not been checked"));
+    }
+
+    #[test]
+    fn witness_keys_match() {
+        check_witness_keys(&commitment("", ""), "5A1E").unwrap();
+    }
+
+    #[test]
+    fn witness_keys_mismatch() {
+        let err = check_witness_keys(&commitment("", ""), "0bad").unwrap_err();
+        assert!(matches!(err, HyperfridgeError::Verification(_)));
+        assert!(err.message().contains("witness keys"));
+    }
+
+    #[test]
+    fn witness_threshold_too_low() {
+        check_witness_threshold(&commitment("", ""), 1).unwrap();
+        let err = check_witness_threshold(&commitment("", ""), 2).unwrap_err();
+        assert!(err.message().contains("at least 2 expected"));
+    }
+
+    #[test]
+    fn fx_key_matches() {
+        let mut commitment = commitment("", "");
+        let err = check_fx_key(&commitment, "ab12").unwrap_err();
+        assert!(err.message().contains("no total in a reporting currency"));
+        commitment.fx = Some(FxTotal {
+            fx_key_fingerprint: "ab12".to_string(),
+            ..Default::default()
+        });
+        check_fx_key(&commitment, "AB12").unwrap();
+        let err = check_fx_key(&commitment, "cd34").unwrap_err();
+        assert!(matches!(err, HyperfridgeError::Verification(_)));
+    }
+
+    #[test]
+    fn fx_key_unsigned_rates() {
+        // accounts in the reporting currency only, no rates signed
+        let mut commitment = commitment("", "");
+        commitment.fx = Some(FxTotal::default());
+        assert!(check_fx_key(&commitment, "").is_err());
+    }
+
+    #[test]
+    fn policy_matches() {
+        let mut commitment = commitment("", "");
+        check_policy(&commitment, "").unwrap();
+        commitment.policy_hash =
+            "d26f5ff7f50c51e02e5d497c0c1dcce4e16f9e451cbdfc5b3a0b453c70d2edda".to_string();
+        check_policy(&commitment, "stmts.ntries=omit\n").unwrap();
+    }
+
+    #[test]
+    fn policy_mismatch() {
+        let err = check_policy(&commitment("", ""), "stmts.ntries=omit\n").unwrap_err();
+        assert!(err.message().contains("disclosure policy"));
+    }
+
+    #[test]
+    fn iban_hash_matches() {
+        let mut commitment = commitment("", "");
+        commitment.stmts.push(Stmt {
+            iban: sha256_hex("4f1c0e6b2a93d8e5CH4308307000289537312"),
+            ..Default::default()
+        });
+        check_iban(
+            &commitment,
+            "CH43 0830 7000 2895 3731 2",
+            "4f1c0e6b2a93d8e5",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn iban_hash_wrong_salt() {
+        let mut commitment = commitment("", "");
+        commitment.accounts = vec![sha256_hex("4f1c0e6b2a93d8e5CH4308307000289537312")];
+        let err = check_iban(&commitment, "CH4308307000289537312", "0000000000000000").unwrap_err();
+        assert!(matches!(err, HyperfridgeError::IbanNotFound(_)));
+        assert!(err.message().contains("is not in the receipt"));
+    }
+}
@@ -0,0 +1,261 @@
+//! Witness signing for EBICS order data.
+//!
+//! The EBICS standard does not (yet) sign the payload of a response, so hyperfridge
+//! relies on a witness who signs the SHA-256 hash of the encrypted `OrderData`. This
+//! replaces the `openssl pkeyutl -sign` step of `data/checkResponse.sh`, so the witness
+//! can run on its own machine (see `witness serve`) and keep an audit trail of every
+//! signature it hands out.
+
+use anyhow::{anyhow, Context};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use ed25519_dalek::Signer;
+use p256::ecdsa::signature::hazmat::PrehashSigner;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Pkcs1v15Sign, Pss, RsaPrivateKey};
+use sha2::{Digest, Sha256};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// `xxd -p` writes 30 bytes per line - we keep the format of checkResponse.sh
+const HEX_LINE_LEN: usize = 60;
+
+/// Signature schemes the guest accepts for witness signatures, names as used by the host
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WitnessScheme {
+    /// RSA with PKCS#1 v1.5 padding and SHA-256 - EBICS A005
+    RsaPkcs1,
+    /// RSA-PSS with SHA-256 - EBICS A006
+    RsaPss,
+    /// ECDSA on P-256 over the SHA-256 hash, DER encoded
+    EcdsaP256,
+    /// Ed25519 with the SHA-256 hash as message
+    Ed25519,
+}
+
+impl WitnessScheme {
+    pub fn from_name(name: &str) -> Result<Self, anyhow::Error> {
+        match name.trim().to_lowercase().as_str() {
+            "rsa-pkcs1" | "a005" => Ok(WitnessScheme::RsaPkcs1),
+            "rsa-pss" | "a006" => Ok(WitnessScheme::RsaPss),
+            "ecdsa-p256" => Ok(WitnessScheme::EcdsaP256),
+            "ed25519" => Ok(WitnessScheme::Ed25519),
+            _ => Err(anyhow!("unknown witness signature scheme {}", name)),
+        }
+    }
+}
+
+/// Private key of the witness matching its signature scheme
+enum SigningKey {
+    Rsa(RsaPrivateKey),
+    EcdsaP256(p256::ecdsa::SigningKey),
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+/// Holds the private key of the witness and where to log signatures
+pub struct Witness {
+    key: SigningKey,
+    scheme: WitnessScheme,
+    audit_log: Option<PathBuf>,
+}
+
+impl Witness {
+    /// Create a witness from a PKCS#8 PEM private key
+    pub fn from_pem(
+        pem: &str,
+        scheme: WitnessScheme,
+        audit_log: Option<PathBuf>,
+    ) -> Result<Self, anyhow::Error> {
+        let key = match scheme {
+            WitnessScheme::RsaPkcs1 | WitnessScheme::RsaPss => SigningKey::Rsa(
+                RsaPrivateKey::from_pkcs8_pem(pem).context("Failed to parse RSA witness key")?,
+            ),
+            WitnessScheme::EcdsaP256 => SigningKey::EcdsaP256(
+                p256::ecdsa::SigningKey::from_pkcs8_pem(pem)
+                    .context("Failed to parse P-256 witness key")?,
+            ),
+            WitnessScheme::Ed25519 => SigningKey::Ed25519(
+                ed25519_dalek::SigningKey::from_pkcs8_pem(pem)
+                    .context("Failed to parse Ed25519 witness key")?,
+            ),
+        };
+        Ok(Witness {
+            key,
+            scheme,
+            audit_log,
+        })
+    }
+
+    /// Signs the order data of `input` and returns the signature in the format of the
+    /// `-Witness.hex` file. `input` may be a complete EbicsResponse, the `-OrderData`
+    /// file or the base64 encoded order data. `source` is written to the audit log,
+    /// e.g. the file name or the address of the caller.
+    pub fn sign(&self, input: &str, source: &str) -> Result<String, anyhow::Error> {
+        let order_data_b64 = extract_order_data_b64(input)?;
+        let digest = order_data_digest(&order_data_b64)?;
+        let signature = self
+            .sign_digest(&digest)
+            .context("Failed to sign order data digest")?;
+        self.audit(source, &digest, &signature)?;
+        Ok(to_witness_hex(&signature))
+    }
+
+    /// Sign the SHA-256 hash of the order data with the scheme of the witness
+    fn sign_digest(&self, digest: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+        match (&self.key, self.scheme) {
+            (SigningKey::Rsa(key), WitnessScheme::RsaPss) => {
+                Ok(key.sign_with_rng(&mut rand::thread_rng(), Pss::new::<Sha256>(), digest)?)
+            }
+            (SigningKey::Rsa(key), _) => Ok(key.sign(Pkcs1v15Sign::new::<Sha256>(), digest)?),
+            (SigningKey::EcdsaP256(key), _) => {
+                let signature: p256::ecdsa::Signature = key.sign_prehash(digest)?;
+                Ok(signature.to_der().as_bytes().to_vec())
+            }
+            (SigningKey::Ed25519(key), _) => Ok(key.sign(digest).to_bytes().to_vec()),
+        }
+    }
+
+    /// Append an entry to the audit log: timestamp, source, digest and signature
+    fn audit(&self, source: &str, digest: &[u8], signature: &[u8]) -> Result<(), anyhow::Error> {
+        if let Some(path) = &self.audit_log {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Unable to open audit log {:?}", path))?;
+            writeln!(
+                file,
+                "{}\t{}\t{}\t{}",
+                Utc::now().to_rfc3339(),
+                source.replace(['\t', '\n'], " "),
+                hex::encode(digest),
+                hex::encode(signature)
+            )
+            .with_context(|| format!("Unable to write audit log {:?}", path))?;
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the base64 content of `<OrderData>`; input without such a tag is
+/// treated as base64 order data.
+pub fn extract_order_data_b64(input: &str) -> Result<String, anyhow::Error> {
+    let content = match input.find("<OrderData") {
+        Some(tag_start) => {
+            let start = input[tag_start..]
+                .find('>')
+                .map(|pos| tag_start + pos + 1)
+                .ok_or_else(|| anyhow!("OrderData tag is not closed"))?;
+            let end = input[start..]
+                .find("</OrderData>")
+                .map(|pos| start + pos)
+                .ok_or_else(|| anyhow!("No closing tag for OrderData"))?;
+            &input[start..end]
+        }
+        None => input,
+    };
+    // same cleanup as checkResponse.sh, carriage returns may be escaped in the XML
+    let order_data_b64: String = content
+        .replace("&#13;", "")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if order_data_b64.is_empty() {
+        return Err(anyhow!("No order data found"));
+    }
+    Ok(order_data_b64)
+}
+
+/// SHA-256 over the (still encrypted) binary order data - same as the guest computes
+pub fn order_data_digest(order_data_b64: &str) -> Result<Vec<u8>, anyhow::Error> {
+    let order_data_bin = general_purpose::STANDARD
+        .decode(order_data_b64)
+        .context("Order data is not valid base64")?;
+    Ok(Sha256::digest(order_data_bin).to_vec())
+}
+
+/// Format the signature like `xxd -p` does
+pub fn to_witness_hex(signature: &[u8]) -> String {
+    let hex = hex::encode(signature);
+    let mut result = String::with_capacity(hex.len() + hex.len() / HEX_LINE_LEN + 1);
+    for line in hex.as_bytes().chunks(HEX_LINE_LEN) {
+        result.push_str(std::str::from_utf8(line).expect("hex is ascii"));
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{extract_order_data_b64, order_data_digest, Witness, WitnessScheme};
+    use std::fs;
+
+    const TEST_EBICS_FILE: &str = "../data/test/test.xml";
+    const TEST_WITNESS_PRIVATE_KEY: &str = "../data/witness.pem";
+    const TEST_WITNESS_PUBLIC_KEY: &str = "../data/pub_witness.pem";
+
+    // PKCS#1 v1.5 signatures are deterministic, so we must get the shipped test signature
+    #[test]
+    fn sign_test_data() {
+        let witness = Witness::from_pem(
+            &fs::read_to_string(TEST_WITNESS_PRIVATE_KEY).unwrap(),
+            WitnessScheme::RsaPkcs1,
+            None,
+        )
+        .unwrap();
+        let order_data = fs::read_to_string(TEST_EBICS_FILE.to_string() + "-OrderData").unwrap();
+        let expected = fs::read_to_string(TEST_EBICS_FILE.to_string() + "-Witness.hex").unwrap();
+
+        let witness_hex = witness.sign(&order_data, "test").unwrap();
+        assert_eq!(witness_hex.trim(), expected.trim());
+    }
+
+    #[test]
+    fn extract_from_response() {
+        let b64 =
+            extract_order_data_b64("<body><OrderData>ab\n cd&#13;</OrderData></body>").unwrap();
+        assert_eq!(b64, "abcd");
+        assert_eq!(extract_order_data_b64("abcd").unwrap(), "abcd");
+        assert!(extract_order_data_b64("<OrderData></OrderData>").is_err());
+    }
+
+    #[test]
+    fn sign_rsa_pss() {
+        use rsa::pkcs8::DecodePublicKey;
+        use rsa::{Pss, RsaPublicKey};
+        use sha2::Sha256;
+
+        let witness = Witness::from_pem(
+            &fs::read_to_string(TEST_WITNESS_PRIVATE_KEY).unwrap(),
+            WitnessScheme::RsaPss,
+            None,
+        )
+        .unwrap();
+        let order_data = fs::read_to_string(TEST_EBICS_FILE.to_string() + "-OrderData").unwrap();
+        let witness_hex = witness.sign(&order_data, "test").unwrap();
+
+        let pub_witness = RsaPublicKey::from_public_key_pem(
+            &fs::read_to_string(TEST_WITNESS_PUBLIC_KEY).unwrap(),
+        )
+        .unwrap();
+        let digest = order_data_digest(&extract_order_data_b64(&order_data).unwrap()).unwrap();
+        let signature = hex::decode(witness_hex.replace('\n', "")).unwrap();
+        pub_witness
+            .verify(Pss::new::<Sha256>(), &digest, &signature)
+            .expect("PSS signature of witness is invalid");
+    }
+
+    #[test]
+    fn scheme_names() {
+        assert_eq!(
+            WitnessScheme::from_name("A006").unwrap(),
+            WitnessScheme::RsaPss
+        );
+        assert_eq!(
+            WitnessScheme::from_name("ed25519").unwrap(),
+            WitnessScheme::Ed25519
+        );
+        assert!(WitnessScheme::from_name("dsa").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+//! Witness for hyperfridge: signs the order data of EBICS responses.
+//!
+//! ```bash
+//! # sign a pre-processed response, writes ../data/test/test.xml-Witness.hex
+//! witness sign --request ../data/test/test.xml-OrderData --key ../data/witness.pem
+//! # run the witness as a service, POST the response (or its OrderData) to /witness
+//! witness serve --key ../data/witness.pem --listen 127.0.0.1:8036 --audit-log witness.log
+//! curl --data-binary @../data/test/test.xml-OrderData http://127.0.0.1:8036/witness
+//! ```
+
+use clap::{Parser, Subcommand};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tiny_http::{Method, Response, Server};
+use witness::{Witness, WitnessScheme};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// largest request body `serve` accepts, EBICS responses are well below
+const MAX_BODY_BYTES: u64 = 16 * 1024 * 1024;
+
+macro_rules! v {
+    ($($arg:tt)*) => {
+        if VERBOSE.load(Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+fn main() {
+    let cli = parse_cli();
+
+    if cli.markdown_help {
+        clap_markdown::print_help_markdown::<Cli>();
+        std::process::exit(0);
+    }
+
+    match &cli.command {
+        Some(Commands::Sign {
+            request,
+            key,
+            scheme,
+            output,
+            audit_log,
+        }) => {
+            let witness = load_witness(key, scheme, audit_log);
+            let input = fs::read_to_string(request)
+                .unwrap_or_else(|_| panic!("Failed to read file at {:?}", request));
+            let witness_hex = witness
+                .sign(&input, &request.to_string_lossy())
+                .unwrap_or_else(|e| panic!("Signing order data failed: {:#}", e));
+
+            // checkResponse.sh and host expect <response>-Witness.hex
+            let output = output.clone().unwrap_or_else(|| {
+                let request = request.to_string_lossy();
+                let stem = request.strip_suffix("-OrderData").unwrap_or(&request);
+                PathBuf::from(format!("{}-Witness.hex", stem))
+            });
+            fs::write(&output, &witness_hex)
+                .unwrap_or_else(|_| panic!("Unable to write file {:?}", output));
+            v!("wrote witness signature to {:?}", output);
+        }
+        Some(Commands::Serve {
+            key,
+            scheme,
+            listen,
+            audit_log,
+        }) => {
+            let witness = load_witness(key, scheme, audit_log);
+            serve(&witness, listen);
+        }
+        None => {
+            panic!("No command given");
+        }
+    }
+}
+
+fn load_witness(key: &PathBuf, scheme: &str, audit_log: &Option<PathBuf>) -> Witness {
+    let pem =
+        fs::read_to_string(key).unwrap_or_else(|_| panic!("Failed to read key file {:?}", key));
+    let scheme = WitnessScheme::from_name(scheme).unwrap_or_else(|e| panic!("{:#}", e));
+    Witness::from_pem(&pem, scheme, audit_log.clone())
+        .unwrap_or_else(|e| panic!("Failed to load witness key: {:#}", e))
+}
+
+/// Minimal HTTP endpoint - `POST /witness` with the EbicsResponse or its OrderData
+/// as body returns the content of the `-Witness.hex` file.
+fn serve(witness: &Witness, listen: &str) {
+    let server =
+        Server::http(listen).unwrap_or_else(|e| panic!("Unable to listen on {}: {}", listen, e));
+    println!("witness listening on http://{}/witness", listen);
+
+    for mut request in server.incoming_requests() {
+        let source = request
+            .remote_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        v!("{} {} from {}", request.method(), request.url(), &source);
+
+        let response = if request.method() != &Method::Post || request.url() != "/witness" {
+            Response::from_string("not found, use POST /witness").with_status_code(404)
+        } else {
+            // read one byte more than allowed to detect a body which is too large
+            let body = std::io::Read::take(request.as_reader(), MAX_BODY_BYTES + 1);
+            match std::io::read_to_string(body) {
+                Ok(body) if body.len() as u64 > MAX_BODY_BYTES => Response::from_string(format!(
+                    "request body larger than {} bytes",
+                    MAX_BODY_BYTES
+                ))
+                .with_status_code(413),
+                Ok(body) => match witness.sign(&body, &source) {
+                    Ok(witness_hex) => Response::from_string(witness_hex),
+                    Err(e) => Response::from_string(format!("{:#}", e)).with_status_code(400),
+                },
+                Err(e) => Response::from_string(e.to_string()).with_status_code(400),
+            }
+        };
+        if let Err(e) = request.respond(response) {
+            eprintln!("Failed to send response to {}: {}", &source, e);
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+#[clap(version = "1.0", author = "Hyperfridge")]
+#[command(arg_required_else_help(true))]
+struct Cli {
+    #[arg(
+        short,
+        long,
+        default_value = "false",
+        help = "verbose mode, false will only print errors"
+    )]
+    verbose: bool,
+    #[arg(long, hide = true)]
+    markdown_help: bool,
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+// https://docs.rs/clap/latest/clap/struct.Arg.html
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Signs the order data of a single EBICS response and writes the -Witness.hex file.
+    Sign {
+        #[arg(
+            short,
+            long,
+            help = "The ebics response (XML), its -OrderData file or the base64 order data.",
+            value_name = "FILE",
+            required = true
+        )]
+        request: PathBuf,
+        #[arg(
+            short,
+            long,
+            help = "PEM (PKCS#8) for the private key of the witness.",
+            value_name = "FILE",
+            required = true
+        )]
+        key: PathBuf,
+        #[arg(
+            short,
+            long,
+            default_value = "rsa-pkcs1",
+            value_parser = clap::builder::PossibleValuesParser::new(["rsa-pkcs1", "rsa-pss", "ecdsa-p256", "ed25519"]),
+            help = "Signature scheme, the host needs the same --witness-scheme."
+        )]
+        scheme: String,
+        #[arg(
+            short,
+            long,
+            help = "Where to write the signature, defaults to <request>-Witness.hex.",
+            value_name = "FILE"
+        )]
+        output: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            help = "Append an entry for each signature to this file.",
+            value_name = "FILE"
+        )]
+        audit_log: Option<PathBuf>,
+    },
+    /// Runs the witness as HTTP service: POST /witness returns the -Witness.hex content.
+    Serve {
+        #[arg(
+            short,
+            long,
+            help = "PEM (PKCS#8) for the private key of the witness.",
+            value_name = "FILE",
+            required = true
+        )]
+        key: PathBuf,
+        #[arg(
+            short,
+            long,
+            default_value = "rsa-pkcs1",
+            value_parser = clap::builder::PossibleValuesParser::new(["rsa-pkcs1", "rsa-pss", "ecdsa-p256", "ed25519"]),
+            help = "Signature scheme, the host needs the same --witness-scheme."
+        )]
+        scheme: String,
+        #[arg(
+            short,
+            long,
+            default_value = "127.0.0.1:8036",
+            help = "Address to listen on."
+        )]
+        listen: String,
+        #[arg(
+            short,
+            long,
+            help = "Append an entry for each signature to this file.",
+            value_name = "FILE"
+        )]
+        audit_log: Option<PathBuf>,
+    },
+}
+
+fn parse_cli() -> Cli {
+    let cli = Cli::parse();
+    VERBOSE.store(cli.verbose, Ordering::Relaxed);
+    cli
+}